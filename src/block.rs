@@ -0,0 +1,189 @@
+//! Layout-stable views over the fields of OCaml blocks
+//!
+//! The fields of an OCaml block are stored as consecutive words, each of them
+//! being an OCaml value. Since a reference to an OCaml value is represented by
+//! the value itself, the fields of a block of size `N` can be viewed as a
+//! `#[repr(C)]` structure of `N` references, which is what the `BlockN` types
+//! of this module are. Unlike Rust tuples, which layout is unspecified, those
+//! types are guaranteed to match the layout of OCaml blocks.
+//!
+//! Those types are typically used in the `View` types of the bindings, and can
+//! be destructured in patterns:
+//!
+//! ```
+//! match match_(lst) {
+//!     Ok(Matcher::Block(View::Cons(&Block2(hd, tl)))) => ... ,
+//!     ...
+//! }
+//! ```
+//!
+//! Views over blocks of size 1 to 12 are provided. The [`ml_block`] macro
+//! selects the view of the right size from a list of field types or patterns.
+
+macro_rules! block {
+    ( $(#[$attr:meta])* $name:ident ( $( $i:tt $field:ident $ty:ident ),+ ) ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        pub struct $name<'a, $($ty: 'a),+>($(pub &'a $ty),+);
+
+        impl<'a, $($ty: 'a),+> $name<'a, $($ty),+> {
+            $( pub fn $field(&self) -> &'a $ty { self.$i } )+
+        }
+    }
+}
+
+block! {
+    /// View over the fields of a block of size 1.
+    Block1(0 field0 A)
+}
+
+block! {
+    /// View over the fields of a block of size 2.
+    Block2(0 field0 A, 1 field1 B)
+}
+
+block! {
+    /// View over the fields of a block of size 3.
+    Block3(0 field0 A, 1 field1 B, 2 field2 C)
+}
+
+block! {
+    /// View over the fields of a block of size 4.
+    Block4(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D)
+}
+
+block! {
+    /// View over the fields of a block of size 5.
+    Block5(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E)
+}
+
+block! {
+    /// View over the fields of a block of size 6.
+    Block6(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F)
+}
+
+block! {
+    /// View over the fields of a block of size 7.
+    Block7(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F, 6 field6 G)
+}
+
+block! {
+    /// View over the fields of a block of size 8.
+    Block8(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F, 6 field6 G, 7 field7 H)
+}
+
+block! {
+    /// View over the fields of a block of size 9.
+    Block9(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F, 6 field6 G, 7 field7 H, 8 field8 I)
+}
+
+block! {
+    /// View over the fields of a block of size 10.
+    Block10(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F, 6 field6 G, 7 field7 H, 8 field8 I, 9 field9 J)
+}
+
+block! {
+    /// View over the fields of a block of size 11.
+    Block11(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F, 6 field6 G, 7 field7 H, 8 field8 I, 9 field9 J,
+           10 field10 K)
+}
+
+block! {
+    /// View over the fields of a block of size 12.
+    Block12(0 field0 A, 1 field1 B, 2 field2 C, 3 field3 D, 4 field4 E,
+           5 field5 F, 6 field6 G, 7 field7 H, 8 field8 I, 9 field9 J,
+           10 field10 K, 11 field11 L)
+}
+
+/// Selects the view over a block from its fields.
+///
+/// In type position, `ml_block!(type 'a; A, B)` expands to `Block2<'a, A, B>`.
+/// In pattern position, `ml_block!(pat a, b)` expands to `Block2(a, b)`.
+#[macro_export] macro_rules! ml_block {
+    (type $lt:lifetime; $a:ty) => {
+        $crate::block::Block1<$lt, $a>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty) => {
+        $crate::block::Block2<$lt, $a, $b>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty) => {
+        $crate::block::Block3<$lt, $a, $b, $c>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty) => {
+        $crate::block::Block4<$lt, $a, $b, $c, $d>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty) => {
+        $crate::block::Block5<$lt, $a, $b, $c, $d, $e>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty) => {
+        $crate::block::Block6<$lt, $a, $b, $c, $d, $e, $f>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty, $g:ty) => {
+        $crate::block::Block7<$lt, $a, $b, $c, $d, $e, $f, $g>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty, $g:ty, $h:ty) => {
+        $crate::block::Block8<$lt, $a, $b, $c, $d, $e, $f, $g, $h>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty, $g:ty, $h:ty,
+                        $i:ty) => {
+        $crate::block::Block9<$lt, $a, $b, $c, $d, $e, $f, $g, $h, $i>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty, $g:ty, $h:ty,
+                        $i:ty, $j:ty) => {
+        $crate::block::Block10<$lt, $a, $b, $c, $d, $e, $f, $g, $h, $i, $j>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty, $g:ty, $h:ty,
+                        $i:ty, $j:ty, $k:ty) => {
+        $crate::block::Block11<$lt, $a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k>
+    };
+    (type $lt:lifetime; $a:ty, $b:ty, $c:ty, $d:ty, $e:ty, $f:ty, $g:ty, $h:ty,
+                        $i:ty, $j:ty, $k:ty, $l:ty) => {
+        $crate::block::Block12<$lt, $a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k,
+                                $l>
+    };
+    (pat $a:pat) => {
+        $crate::block::Block1($a)
+    };
+    (pat $a:pat, $b:pat) => {
+        $crate::block::Block2($a, $b)
+    };
+    (pat $a:pat, $b:pat, $c:pat) => {
+        $crate::block::Block3($a, $b, $c)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat) => {
+        $crate::block::Block4($a, $b, $c, $d)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat) => {
+        $crate::block::Block5($a, $b, $c, $d, $e)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat) => {
+        $crate::block::Block6($a, $b, $c, $d, $e, $f)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat, $g:pat) => {
+        $crate::block::Block7($a, $b, $c, $d, $e, $f, $g)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat, $g:pat, $h:pat) => {
+        $crate::block::Block8($a, $b, $c, $d, $e, $f, $g, $h)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat, $g:pat, $h:pat, $i:pat) => {
+        $crate::block::Block9($a, $b, $c, $d, $e, $f, $g, $h, $i)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat, $g:pat, $h:pat, $i:pat,
+         $j:pat) => {
+        $crate::block::Block10($a, $b, $c, $d, $e, $f, $g, $h, $i, $j)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat, $g:pat, $h:pat, $i:pat,
+         $j:pat, $k:pat) => {
+        $crate::block::Block11($a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k)
+    };
+    (pat $a:pat, $b:pat, $c:pat, $d:pat, $e:pat, $f:pat, $g:pat, $h:pat, $i:pat,
+         $j:pat, $k:pat, $l:pat) => {
+        $crate::block::Block12($a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k, $l)
+    };
+}
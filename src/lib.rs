@@ -7,7 +7,10 @@
 #[macro_use] pub mod raw;
 #[macro_use] pub mod mem;
 #[macro_use] pub mod macros;
+#[macro_use] pub mod block;
 #[macro_use] pub mod matching;
+#[macro_use] pub mod types;
+pub mod record;
 #[macro_use] pub mod stdlib;
 
 // Tests in this module will only test the API and never actually run when
@@ -19,21 +22,645 @@
 
 #[cfg(test)] pub mod map {
     use mem::{Gc, P};
-    use matching::match_;
+    use stdlib::list::from_iter;
     use stdlib::pervasives::*;
 
     ml_extern! {
         fn caml_map(lst: List<int>) -> List<int> = map;
+        fn caml_nth_from_end(lst: List<int>, n: int) -> Option<int> = nth_from_end;
     }
 
     fn map<'a>(gc: &'a mut Gc, lst: &'a List<int>) -> &'a List<int> {
-        match match_(lst) {
-            list![] => lst,
-            list![hd :: tl] => {
-                local!{ let rec = map(gc, tl); }
-                alloc!(gc: Cons(hd + int::from(1), rec.as_ref()))
+        let succ: Vec<int> = lst.iter().map(|x| int::from(x) + int::from(1)).collect();
+        alloc!(gc: from_iter(succ))
+    }
+
+    fn nth_from_end<'a>(gc: &'a mut Gc, lst: &'a List<int>, n: &'a int) -> &'a Option<int> {
+        let n = isize::from(int::from(n)) as usize;
+        match lst.len().checked_sub(n + 1).and_then(|i| lst.nth(i)) {
+            ::std::option::Option::Some(x) => alloc!(gc: Some(int::from(x))),
+            ::std::option::Option::None => alloc!(gc: None())
+        }
+    }
+}
+
+#[cfg(test)] pub mod last {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_last(lst: List<Option<int>>) -> int = last;
+        fn caml_sum_heads(lists: List<List<int>>) -> int = sum_heads;
+    }
+
+    // Returns x if the last element of the list is Some(x), -1 if it is None,
+    // and 0 if the list is empty or has more than 1000 elements.
+    fn last<'a>(gc: &'a mut Gc, lst: &'a List<Option<int>>) -> &'a int {
+        let mut lst = lst;
+        let mut n = 0;
+
+        loop {
+            lst = ml_match! { lst;
+                list![Some(x) :: list![]] => return x,
+                list![None :: list![]] => return alloc!(gc: int::from(-1)),
+                list![_ :: tl] if n < 1000 => tl,
+                _ => return alloc!(gc: int::from(0)),
+            };
+
+            n += 1;
+        }
+    }
+
+    // Sums the heads of the lists, skipping the empty ones, up to the first
+    // negative head.
+    fn sum_heads<'a>(gc: &'a mut Gc, lists: &'a List<List<int>>) -> &'a int {
+        let mut sum = 0;
+
+        'lists: for l in lists {
+            ml_match! { l;
+                list![] => continue 'lists,
+                list![hd :: _] if int::from(hd) < int::from(0) => break 'lists,
+                list![hd :: _] => sum += isize::from(int::from(hd)),
+            }
+        }
+
+        alloc!(gc: int::from(sum))
+    }
+}
+
+#[cfg(test)] pub mod greet {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_greet(name: MlString) -> MlString = greet;
+    }
+
+    fn greet<'a>(gc: &'a mut Gc, name: &'a MlString) -> &'a MlString {
+        // The greeting is computed before allocating, as the allocation might
+        // move the name.
+        let greeting = match name.to_str(gc) {
+            Ok(name) => format!("Hello, {}!", name),
+            Err(_) => String::from("Hello, stranger!")
+        };
+
+        alloc!(gc: greeting)
+    }
+}
+
+#[cfg(test)] pub mod rot13 {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_rot13(buf: Bytes) -> Bytes = rot13;
+    }
+
+    // Applies rot13 to the buffer, in place.
+    fn rot13<'a>(gc: &'a mut Gc, buf: &'a Bytes) -> &'a Bytes {
+        for b in buf.as_mut_bytes(gc) {
+            *b = match *b {
+                b'a' ..= b'z' => (*b - b'a' + 13) % 26 + b'a',
+                b'A' ..= b'Z' => (*b - b'A' + 13) % 26 + b'A',
+                _ => *b
+            }
+        }
+
+        buf
+    }
+}
+
+#[cfg(test)] pub mod shape {
+    use mem::{Gc, P};
+    use matching::match_;
+    use stdlib::pervasives::*;
+
+    ml_type! {
+        ::shape => type shape = Empty | Circle of int | Rect of int * int;
+    }
+
+    ml_extern! {
+        fn caml_scale(s: shape) -> shape = scale;
+    }
+
+    fn scale<'a>(gc: &'a mut Gc, s: &'a shape) -> &'a shape {
+        match match_(s) {
+            shape![Empty] => s,
+            shape![Circle(r)] => alloc!(gc: Circle(int::from(r) * int::from(2))),
+            shape![Rect(w, h)] => {
+                alloc!(gc: Rect(int::from(w) * int::from(2), int::from(h) * int::from(2)))
+            }
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+#[cfg(test)] pub mod point {
+    use mem::{Gc, P};
+    use record::Record;
+    use stdlib::pervasives::*;
+
+    ml_type! {
+        type point = { x : int; mutable y : int };
+    }
+
+    ml_extern! {
+        fn caml_mirror(p: point) -> point = mirror;
+    }
+
+    // Swaps the coordinates of p, the x one through a functional update.
+    fn mirror<'a>(gc: &'a mut Gc, p: &'a point) -> &'a point {
+        let y = p.y();
+        p.set(field::y, p.x());
+        alloc!(gc: p.with(field::x, y))
+    }
+}
+
+#[cfg(test)] pub mod norm {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_norm(v: List<float>) -> Option<float> = norm;
+    }
+
+    // Computes the euclidean norm of a 2D vector, if the list has two elements.
+    fn norm<'a>(gc: &'a mut Gc, v: &'a List<float>) -> &'a Option<float> {
+        let n = ml_match! { v;
+            list![x :: list![y :: list![]]] => {
+                let (x, y) = (float::from(x), float::from(y));
+                ::std::option::Option::Some(f64::from(x * x + y * y).sqrt())
+            }
+            _ => ::std::option::Option::None,
+        };
+
+        match n {
+            ::std::option::Option::Some(n) => alloc!(gc: Some(float::from(n))),
+            ::std::option::Option::None => alloc!(gc: None())
+        }
+    }
+}
+
+#[cfg(test)] pub mod cumsum {
+    use mem::{Gc, P};
+    use stdlib::pervasives::{Array, float};
+
+    ml_extern! {
+        fn caml_cumsum(arr: Array<float>) -> Array<float> = cumsum;
+        fn caml_max_float(arr: Array<float>) -> float = max_float;
+    }
+
+    // Computes the cumulative sums of a flat float array.
+    fn cumsum<'a>(gc: &'a mut Gc, arr: &'a Array<float>) -> &'a Array<float> {
+        let sums: Vec<f64> = match arr.as_slice(gc) {
+            Some(s) => s.iter().scan(0., |acc, &x| { *acc += x; Some(*acc) }).collect(),
+            None => return arr
+        };
+
+        alloc!(gc: &sums[..])
+    }
+
+    // Works whatever the representation of float arrays.
+    fn max_float<'a>(gc: &'a mut Gc, arr: &'a Array<float>) -> &'a float {
+        let max = arr.floats().fold(f64::NEG_INFINITY, |max, x| max.max(f64::from(x)));
+        alloc!(gc: float::from(max))
+    }
+}
+
+#[cfg(test)] pub mod squares {
+    use mem::{Gc, P};
+    use stdlib::array::from_iter;
+    use stdlib::pervasives::{Array, int};
+
+    ml_extern! {
+        fn caml_squares(arr: Array<int>) -> Array<int> = squares;
+    }
+
+    // Returns the squares of the elements of arr, which is reversed in place.
+    fn squares<'a>(gc: &'a mut Gc, arr: &'a Array<int>) -> &'a Array<int> {
+        let len = arr.len();
+        for i in 0..len / 2 {
+            let (x, y) = (arr.get(i), arr.get(len - 1 - i));
+            arr.set(i, y);
+            arr.set(len - 1 - i, x);
+        }
+
+        let squares: Vec<int> = arr.iter().map(|x| int::from(x) * int::from(x)).collect();
+        alloc!(gc: from_iter(squares))
+    }
+}
+
+#[cfg(test)] pub mod divmod {
+    use mem::{Gc, P};
+    use matching::match_;
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_divmod(p: Tuple2<int, int>) -> Tuple2<int, int> = divmod;
+    }
+
+    // Returns both the quotient and the remainder of a division.
+    fn divmod<'a>(gc: &'a mut Gc, p: &'a Tuple2<int, int>) -> &'a Tuple2<int, int> {
+        match match_(p) {
+            tuple!(a, b) => {
+                let (a, b) = (int::from(a), int::from(b));
+                alloc!(gc: (a / b, a % b))
             }
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+#[cfg(test)] pub mod parse {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+    use stdlib::result::{self, Ok, Error};
+
+    ml_extern! {
+        fn caml_parse(s: MlString) -> result::Result<int, MlString> = parse;
+        fn caml_incr(r: Ref<int>) -> Ref<int> = incr;
+    }
+
+    // Parses an integer, returning the error message on failure.
+    fn parse<'a>(gc: &'a mut Gc, s: &'a MlString) -> &'a result::Result<int, MlString> {
+        let res = s.to_str(gc).map_err(|e| e.to_string())
+            .and_then(|s| s.parse::<isize>().map_err(|e| e.to_string()));
+
+        match res {
+            ::std::result::Result::Ok(n) => alloc!(gc: Ok(int::from(n))),
+            ::std::result::Result::Err(e) => alloc!(gc: Error(e))
+        }
+    }
+
+    // Increments the content of r, in place.
+    fn incr<'a>(gc: &'a mut Gc, r: &'a Ref<int>) -> &'a Ref<int> {
+        let n = int::from(r.get()) + int::from(1);
+        r.set(alloc!(gc: n));
+        r
+    }
+}
+
+#[cfg(test)] pub mod color {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_enum! {
+        type color = Red | Green | Blue => Color;
+    }
+
+    ml_extern! {
+        fn caml_next_color(c: color) -> color = next;
+        fn caml_is_upper(c: MlChar) -> MlBool = is_upper;
+        fn caml_to_upper(c: Uchar) -> Uchar = to_upper;
+    }
+
+    fn next<'a>(gc: &'a mut Gc, c: &'a color) -> &'a color {
+        match Color::from(c) {
+            Color::Red => alloc!(gc: Color::Green),
+            Color::Green => alloc!(gc: Color::Blue),
+            Color::Blue => alloc!(gc: Color::Red)
+        }
+    }
+
+    fn is_upper<'a>(gc: &'a mut Gc, c: &'a MlChar) -> &'a MlBool {
+        let c = u8::from(MlChar::from(c));
+        alloc!(gc: MlBool::from(c.is_ascii_uppercase()))
+    }
+
+    // Only handles characters which uppercase is a single character.
+    fn to_upper<'a>(gc: &'a mut Gc, c: &'a Uchar) -> &'a Uchar {
+        let c = char::from(Uchar::from(c));
+        let mut upper = c.to_uppercase();
+        match (upper.next(), upper.next()) {
+            (::std::option::Option::Some(u), ::std::option::Option::None) => {
+                alloc!(gc: Uchar::from(u))
+            }
+            _ => alloc!(gc: Uchar::from(c))
+        }
+    }
+}
+
+#[cfg(test)] pub mod offset {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_offset(base: int64, len: int32) -> int64 = offset;
+    }
+
+    // Computes the end offset of a chunk, rounded up to a multiple of 8.
+    fn offset<'a>(gc: &'a mut Gc, base: &'a int64, len: &'a int32) -> &'a int64 {
+        let len = Int64::from(i64::from(i32::from(Int32::from(len))));
+        let end = Int64::from(base) + len + Int64::from(7);
+        alloc!(gc: end & !Int64::from(7))
+    }
+}
+
+#[cfg(test)] pub mod gcd {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_gcd(a: int, b: int) -> int = gcd;
+        fn caml_count_distinct(lst: List<int>) -> int = count_distinct;
+    }
+
+    fn gcd<'a>(gc: &'a mut Gc, a: &'a int, b: &'a int) -> &'a int {
+        let (mut a, mut b) = (int::from(a), int::from(b));
+        while b != int::from(0) {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+
+        alloc!(gc: if a < int::from(0) { -a } else { a })
+    }
+
+    // Hashes the values converted from the references of the list.
+    fn count_distinct<'a>(gc: &'a mut Gc, lst: &'a List<int>) -> &'a int {
+        let seen: ::std::collections::HashSet<int> = lst.iter().map(int::from).collect();
+        alloc!(gc: int::from(seen.len() as isize))
+    }
+}
+
+#[cfg(test)] pub mod stream {
+    use mem::{Gc, P};
+    use stdlib::pervasives::*;
+
+    ml_type! {
+        ::stream => type stream = End | More of int * stream;
+    }
+
+    ml_extern! {
+        fn caml_range(n: int) -> stream = range;
+    }
+
+    // Builds the stream of the integers from 0 to n - 1, whatever its length.
+    fn range<'a>(gc: &'a mut Gc, n: &'a int) -> &'a stream {
+        let n = isize::from(int::from(n));
+        alloc!(gc: chain::More((0..n).map(int::from), End()))
+    }
+}
+
+#[cfg(test)] pub mod listops {
+    use mem::{Gc, P};
+    use stdlib::list;
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_sorted_evens(lst: List<int>) -> List<int> = sorted_evens;
+        fn caml_rev_append(l1: List<int>, l2: List<int>) -> List<int> = rev_append;
+        fn caml_lookup(lst: List<Tuple2<MlString, int>>, key: MlString) -> Option<int> = lookup;
+    }
+
+    fn sorted_evens<'a>(gc: &'a mut Gc, lst: &'a List<int>) -> &'a List<int> {
+        local! {
+            let evens = list::filter(gc, lst, |x| int::from(x) % int::from(2) == int::from(0));
+        }
+        list::sort(gc, evens.as_ref(), compare)
+    }
+
+    fn rev_append<'a>(gc: &'a mut Gc, l1: &'a List<int>, l2: &'a List<int>) -> &'a List<int> {
+        local! {
+            let rev = list::rev(gc, l1);
+        }
+        list::append(gc, rev.as_ref(), l2)
+    }
+
+    fn lookup<'a>(gc: &'a mut Gc, lst: &'a List<Tuple2<MlString, int>>, key: &'a MlString)
+                  -> &'a Option<int> {
+        match list::assoc(lst, key) {
+            ::std::option::Option::Some(v) => alloc!(gc: Some(v)),
+            ::std::option::Option::None => alloc!(gc: None())
+        }
+    }
+}
+
+#[cfg(test)] pub mod options {
+    use mem::{Gc, P};
+    use matching::match_;
+    use stdlib::pervasives::*;
+    use stdlib::result;
+
+    ml_extern! {
+        fn caml_double_opt(o: Option<int>) -> Option<int> = double_opt;
+        fn caml_head_or_zero(lst: List<Option<int>>) -> int = head_or_zero;
+        fn caml_check_positive(r: result::Result<int, MlString>)
+            -> result::Result<int, MlString> = check_positive;
+    }
+
+    fn double_opt<'a>(gc: &'a mut Gc, o: &'a Option<int>) -> &'a Option<int> {
+        match match_(o) {
+            none!() => o,
+            some!(x) if int::from(x) < int::from(0) => alloc!(gc: None()),
+            some!(_) => o.map(gc, |x| int::from(x) * int::from(2)),
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    fn head_or_zero<'a>(gc: &'a mut Gc, lst: &'a List<Option<int>>) -> &'a int {
+        ml_match! { lst;
+            list![some!(x) :: _] => x,
+            list![none!() :: _] => alloc!(gc: int::from(0)),
+            list![] => alloc!(gc: int::from(-1)),
+        }
+    }
+
+    fn check_positive<'a>(gc: &'a mut Gc, r: &'a result::Result<int, MlString>)
+                          -> &'a result::Result<int, MlString> {
+        r.and_then(gc, |x| {
+            if int::from(x) > int::from(0) { Ok(int::from(x)) } else { Err("not positive") }
+        })
+    }
+}
+
+#[cfg(test)] pub mod exceptions {
+    use mem::{Gc, P};
+    use stdlib::list;
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_find(lst: List<Tuple2<MlString, int>>, key: MlString) -> int = find;
+        fn caml_checked_sqrt(x: float) -> float = checked_sqrt;
+        fn caml_raise_named(name: MlString) -> unit = raise_named;
+    }
+
+    // List.assoc, raising Not_found if the key is missing.
+    fn find<'a>(gc: &'a mut Gc, lst: &'a List<Tuple2<MlString, int>>, key: &'a MlString)
+                -> &'a int {
+        match list::assoc(lst, key) {
+            ::std::option::Option::Some(v) => v,
+            ::std::option::Option::None => gc.raise_not_found()
+        }
+    }
+
+    fn checked_sqrt<'a>(gc: &'a mut Gc, x: &'a float) -> &'a float {
+        let x = f64::from(float::from(x));
+        if x < 0. {
+            gc.invalid_arg("checked_sqrt")
+        }
+        alloc!(gc: float::from(x.sqrt()))
+    }
+
+    // Raises the constant exception registered under the given name.
+    fn raise_named<'a>(gc: &'a mut Gc, name: &'a MlString) -> &'a unit {
+        let exn = match name.to_str(gc) {
+            Ok(name) => Exn::named(gc, name),
+            Err(_) => gc.failwith("invalid exception name")
+        };
+
+        match exn {
+            ::std::option::Option::Some(exn) => gc.raise_constant(exn),
+            ::std::option::Option::None => gc.failwith("unregistered exception")
+        }
+    }
+}
+
+#[cfg(test)] pub mod results {
+    use std::convert::TryFrom;
+    use mem::Gc;
+    use stdlib::pervasives::{int, List, MlString, MlException};
+
+    ml_extern! {
+        fn caml_parse_int(s: MlString) -> int = parse_int;
+        fn caml_head(lst: List<int>) -> int = head;
+    }
+
+    // Raises the `Parse_error` exception registered on the OCaml side.
+    fn parse_int<'a>(gc: &'a mut Gc, s: &'a MlString) -> Result<&'a int, MlException> {
+        let n = s.to_str(gc)?
+                 .parse::<i64>()
+                 .map_err(|e| MlException::Registered("parse_error", Some(e.to_string())))?;
+        Ok(alloc!(gc: int::try_from(n)?))
+    }
+
+    fn head<'a>(gc: &'a mut Gc, lst: &'a List<int>) -> Result<&'a int, MlException> {
+        lst.nth(0).ok_or(MlException::NotFound)
+    }
+}
+
+#[cfg(test)] pub mod panics {
+    use mem::Gc;
+    use stdlib::pervasives::{int, Array};
+
+    ml_extern! {
+        fn caml_get(arr: Array<int>, i: int) -> int = get;
+    }
+
+    // Panics if the index is out of bounds, which raises `Rust_panic`.
+    fn get<'a>(gc: &'a mut Gc, arr: &'a Array<int>, i: &'a int) -> &'a int {
+        arr.get(isize::from(int::from(i)) as usize)
+    }
+}
+
+#[cfg(test)] pub mod closures {
+    use mem::{Gc, P};
+    use stdlib::result;
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_apply_n(f: Closure<int, int>, n: int, x: int) -> int = apply_n;
+        fn caml_try_apply(f: Closure<int, int>, x: int) -> result::Result<int, Exn> = try_apply;
+        fn caml_fold_range(f: Closure2<int, int, int>, n: int, acc: int) -> int = fold_range;
+    }
+
+    // Applies f n times, letting its exceptions through.
+    fn apply_n<'a>(gc: &'a mut Gc, f: &'a Closure<int, int>, n: &'a int, x: &'a int) -> &'a int {
+        local! {
+            let g: P<Closure<int, int>> = f;
+        }
+
+        let mut x = int::from(x);
+        for _ in 0..isize::from(int::from(n)) {
+            x = match g.as_ref().call(gc, x) {
+                Ok(y) => int::from(y),
+                Err(e) => {
+                    local! {
+                        let exn: P<Exn> = e;
+                    }
+                    gc.raise(exn.as_ref())
+                }
+            };
+        }
+        alloc!(gc: x)
+    }
+
+    fn try_apply<'a>(gc: &'a mut Gc, f: &'a Closure<int, int>, x: &'a int)
+                     -> &'a result::Result<int, Exn> {
+        match f.call(gc, x) {
+            Ok(y) => {
+                let y = int::from(y);
+                alloc!(gc: result::Ok(y))
+            }
+            Err(e) => {
+                local! {
+                    let exn: P<Exn> = e;
+                }
+                alloc!(gc: result::Error(exn.as_ref()))
+            }
+        }
+    }
+
+    // Computes f (... (f (f acc 0) 1) ...) (n - 1).
+    fn fold_range<'a>(gc: &'a mut Gc, f: &'a Closure2<int, int, int>, n: &'a int, acc: &'a int)
+                      -> Result<&'a int, MlException> {
+        local! {
+            let g: P<Closure2<int, int, int>> = f;
+        }
+
+        let mut acc = int::from(acc);
+        for i in 0..isize::from(int::from(n)) {
+            acc = match g.as_ref().call(gc, acc, int::from(i)) {
+                Ok(acc) => int::from(acc),
+                Err(_) => return Err(MlException::failure("fold_range"))
+            };
+        }
+        Ok(alloc!(gc: acc))
+    }
+}
+
+#[cfg(test)] pub mod reentrancy {
+    use mem::{Gc, P};
+    use stdlib::list;
+    use stdlib::pervasives::*;
+
+    ml_extern! {
+        fn caml_map_or_zero(f: Closure<int, int>, lst: List<int>) -> List<int> = map_or_zero;
+        fn caml_div(x: int, y: int) -> int = div;
+    }
+
+    // Maps f over the list, replacing its exceptions with 0. f may call back
+    // into any extern, including this one, which may raise with roots
+    // registered.
+    fn map_or_zero<'a>(gc: &'a mut Gc, f: &'a Closure<int, int>, lst: &'a List<int>)
+                       -> &'a List<int> {
+        local! {
+            let g: P<Closure<int, int>> = f;
+            let l: P<List<int>> = lst;
+        }
+
+        let mut res = Vec::new();
+        let mut i = 0;
+        while let ::std::option::Option::Some(x) = l.as_ref().nth(i) {
+            let x = int::from(x);
+            res.push(match g.as_ref().call(gc, x) {
+                Ok(y) => int::from(y),
+                Err(_) => int::from(0)
+            });
+            i += 1;
+        }
+
+        alloc!(gc: list::from_iter(res))
+    }
+
+    // Raises Division_by_zero without going through the Gc, with a root
+    // registered.
+    fn div<'a>(gc: &'a mut Gc, x: &'a int, y: &'a int) -> &'a int {
+        local! {
+            let z: P<Option<int>> = alloc!(gc: Some(int::from(x)));
         }
+        let q = int::from(z.as_ref().unwrap_or(x)) / int::from(y);
+        alloc!(gc: q)
     }
 }
 
@@ -44,7 +671,7 @@
 
     #[no_mangle]
     pub extern "C" fn foo(x: raw::Value) -> raw::Value {
-        let ref mut gc = unsafe { Gc::new() };
+        let gc = &mut unsafe { Gc::new() };
 
         local!{
             let x = alloc!(gc: Some(Some(Some(int::from(43)))));
@@ -46,6 +46,7 @@
     { $( let $binder:ident $( : $ty:ty )* = $e:expr ; )+ } => {
         $(
             let $binder $( : $ty )*;
+            #[allow(clippy::macro_metavars_in_unsafe)]
             unsafe {
                 ($binder) = $crate::mem::P::new();
                 ($binder).register(); // This value is register to the GC and cannot be moved
@@ -96,6 +97,26 @@
 ///   the same validity (which will happen if it's allocated through the given
 ///   `Gc`).
 ///
+/// The implementation function may also return `Result<&'a T, E>`, where `E`
+/// converts into an [`MlException`], in which case errors are raised as OCaml
+/// exceptions, `Failure` by default:
+///
+/// ```
+/// fn count(gc: &'a Gc, list: &'a List<int>) -> Result<&'a int, MlException> {
+///     let n = int::try_from(list.len())?;
+///     Ok(alloc!(gc: n))
+/// }
+/// ```
+///
+/// Panics of the implementation function are caught, and raised as the
+/// `Rust_panic` exception with the panic message, if it was registered on the
+/// OCaml side, or as `Failure` otherwise:
+///
+/// ```
+/// exception Rust_panic of string
+/// let () = Callback.register_exception "rust_panic" (Rust_panic "")
+/// ```
+///
 /// Note: The `count` name on the Rust side is the name of the implementation
 /// function. The `count` name on the OCaml side is the name that will be used
 /// to call the `external`. They do not have to match. The name of the wrapping
@@ -113,18 +134,36 @@
                                -> $ret_ty:ty = $rust_fn:path; )* ) => {
         $(
             #[no_mangle]
+            #[allow(clippy::missing_safety_doc)] // Only ever called by OCaml.
             pub unsafe extern "C" fn $caml_name($($arg_id: $crate::raw::Value),*)
                                                 -> $crate::raw::Value {
+                // The Gc must be created before the arguments are rooted, for
+                // exceptions to unregister them.
+                let mut gc = $crate::mem::Gc::new();
+
                 $(
                     let $arg_id: $crate::mem::P<$arg_ty> =
                         $crate::mem::P::from($arg_id);
                     ($arg_id).register();
-                ),*
-
-                let mut gc = $crate::mem::Gc::new();
-                let ret: &$ret_ty = $rust_fn (&mut gc, $($arg_id.as_ref()),*);
+                )*
+                // Panics must not unwind into the OCaml frames, and are raised
+                // as exceptions instead. The roots registered by the
+                // implementation are unregistered while unwinding.
+                let ret = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+                    // The result is converted to a raw value, to release the
+                    // borrow of the Gc before raising an error.
+                    $crate::stdlib::exn::Return::into_result(
+                        $rust_fn (&mut gc, $($arg_id.as_ref()),*))
+                        .map(|ret: &$ret_ty| -> $crate::raw::Value { ::std::mem::transmute(ret) })
+                }));
 
-                ::std::mem::transmute(ret)
+                match ret {
+                    ::std::result::Result::Ok(::std::result::Result::Ok(ret)) => ret,
+                    ::std::result::Result::Ok(::std::result::Result::Err(exn)) => exn.raise(&mut gc),
+                    ::std::result::Result::Err(panic) => {
+                        $crate::stdlib::exn::MlException::from_panic(panic).raise(&mut gc)
+                    }
+                }
             }
         )*
     }
@@ -16,19 +16,28 @@
 //!     let value = alloc!{ gc | Cons(1, Cons(2, Cons(3, Nil))) };
 //! }
 //!
-//! match value.to() {
+//! match match_(value.as_ref()) {
 //!     list![] => ... ,              // The value is Nil
 //!     list![ hd :: tl ] => ... ,    // The value is a Cons of hd and tl
+//!     Err(e) => ... ,               // The value is not a valid list
 //! }
 //! ```
 //!
-//! Unfortunately, nested patterns aren't allowed yet with this mechanism, as it
-//! would require either being able to call `.to()` on subpatterns, are to
-//! deeply-convert the structure (which is costly). This might evolve in the
-//! future if Rust ever has automatic calls to AsRef or Deref in
-//! pattern-matchings, but in the meantime, nesting `match` blocks will be
-//! required to deeply-inspect a structure.
+//! Unfortunately, nested patterns aren't allowed with this mechanism, as it
+//! would require either being able to call `match_` on subpatterns, are to
+//! deeply-convert the structure (which is costly). Nested patterns are instead
+//! provided by the [`ml_match`] macro, which compiles them into a sequence of
+//! shallow matches:
+//!
+//! ```
+//! ml_match! { value.as_ref();
+//!     list![Some(x) :: list![]] => ... ,
+//!     list![_ :: tl] if ... => ... ,
+//!     _ => ... ,
+//! }
+//! ```
 
+use std::convert::TryFrom;
 #[macro_use] use raw;
 
 /// A type allowing inspection of an OCaml value.
@@ -36,29 +45,303 @@
 /// This type ‶describes″ the content of an OCaml value. A typical OCaml value
 /// (enum, record, ...) is represented either as an unboxed integer or as a
 /// boxed block containing a tag and fields.
-pub enum Matcher<'a, T> where T: Match<'a>, T::BlockValue: 'a {
-    Inline(T::InlineTag),
-    Block(T::BlockTag, &'a T::BlockValue)
+///
+/// For a value of type `T`, the `Inline` case holds a `T::InlineTag` and the
+/// `Block` case holds a `T::Block`, which is a view over the fields of the
+/// block specific to its constructor.
+pub enum Matcher<I, B> {
+    Inline(I),
+    Block(B)
+}
+
+/// Error returned when matching a value which is not valid for its type.
+///
+/// This happens when a value of the wrong type is passed from the OCaml side,
+/// typically through the use of `Obj.magic`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Invalid {
+    /// An immediate value which is not a constant constructor of the type.
+    Inline(isize),
+    /// A block which tag is not the one of a non-constant constructor of the
+    /// type.
+    Block { tag: u8, size: usize }
+}
+
+impl ::std::fmt::Display for Invalid {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            Invalid::Inline(i) => write!(fmt, "invalid immediate value {}", i),
+            Invalid::Block { tag, size } => {
+                write!(fmt, "invalid block of tag {} and size {}", tag, size)
+            }
+        }
+    }
+}
+
+impl ::std::error::Error for Invalid {}
+
+/// Tag of the types which have no constructor of a given kind.
+///
+/// This is used for instance as the `BlockTag` of the types which values are
+/// all immediate: as it has no value, converting any tag into it fails.
+pub enum NoTag {}
+
+impl TryFrom<isize> for NoTag {
+    type Error = ();
+
+    fn try_from(x: isize) -> Result<NoTag, ()> {
+        Err(())
+    }
 }
 
-pub fn match_<'a, T: Match<'a>>(val: &'a T) -> Matcher<'a, T> {
-    let raw::Value(raw) = unsafe { ::std::mem::transmute(val) };
+impl TryFrom<u8> for NoTag {
+    type Error = ();
+
+    fn try_from(x: u8) -> Result<NoTag, ()> {
+        Err(())
+    }
+}
+
+/// Converts a value into a [`Matcher`].
+///
+/// Values which tag does not correspond to a constructor of their type are
+/// reported as [`Invalid`] instead. The matcher macros of each type expand to
+/// patterns matching the result of this function:
+///
+/// ```
+/// match match_(value) {
+///     list![] => ... ,
+///     list![hd :: tl] => ... ,
+///     Err(e) => ... ,
+/// }
+/// ```
+pub fn match_<'a, T: Match<'a>>(val: &'a T) -> Result<Matcher<T::InlineTag, T::Block>, Invalid> {
+    let raw::Value(raw) = unsafe { ::std::mem::transmute::<&T, raw::Value>(val) };
     if raw & 1 == 0 {
         // Pointer
         unsafe {
-            let hdr_p = ::std::mem::transmute::<_, *mut usize>(raw).offset(-1);
-            let tag = T::BlockTag::from(*(hdr_p as *mut u8));
-            let data_p = ::std::mem::transmute(raw);
-            Matcher::Block(tag, data_p)
+            let hdr = raw::header(raw::Value(raw));
+            let (tag, size) = (raw::tag(hdr), raw::wosize(hdr));
+            match T::BlockTag::try_from(tag) {
+                Ok(tag) => Ok(Matcher::Block(T::view(tag, val))),
+                Err(_) => Err(Invalid::Block { tag, size })
+            }
         }
     } else {
         // unboxed tag
-        Matcher::Inline(T::InlineTag::from(int_val!(raw)))
+        match T::InlineTag::try_from(int_val!(raw)) {
+            Ok(tag) => Ok(Matcher::Inline(tag)),
+            Err(_) => Err(Invalid::Inline(int_val!(raw)))
+        }
     }
 }
 
-pub unsafe trait Match<'a> {
-    type InlineTag: From<isize>;
-    type BlockTag: From<u8>;
-    type BlockValue;
+/// The trait of OCaml types which values can be matched.
+///
+/// The `Block` type is typically an enum with one case per non-constant
+/// constructor of the OCaml type, each holding a reference to the fields of
+/// the block, viewed through one of the types of the [`block`] module:
+///
+/// ```
+/// pub enum View<'a, T: 'a> {
+///     Cons(&'a Block2<'a, T, List<T>>)
+/// }
+/// ```
+///
+/// # Safety
+///
+/// `view` is called on any value of type `Self` which tag converts into a
+/// `BlockTag`, and must only read the fields such blocks are known to have.
+pub unsafe trait Match<'a>: Sized {
+    type InlineTag: TryFrom<isize>;
+    type BlockTag: TryFrom<u8>;
+    type Block;
+
+    /// Creates the view over the fields of `val`, which is expected to be a
+    /// block with the given tag.
+    ///
+    /// This function should probably not be used directly, but rather through
+    /// [`match_`].
+    ///
+    /// # Safety
+    ///
+    /// `val` must be a block with the given tag.
+    unsafe fn view(tag: Self::BlockTag, val: &'a Self) -> Self::Block;
+}
+
+/// Matches an OCaml value against nested patterns.
+///
+/// This macro takes a reference to an OCaml value followed by a list of arms,
+/// just like a `match` expression. Patterns can be made of:
+///
+/// * The wildcard pattern `_`, or an identifier which is then bound to a
+///   reference to the matched value. Identifiers starting with an uppercase
+///   letter are rejected, as constant constructors must be matched through the
+///   macro of their type, such as `tree![Leaf]`.
+/// * The `Some(p)` and `None` patterns for OCaml options.
+/// * The matcher macros of other types, such as `list![]`, `list![p1 :: p2]`
+///   or the macros generated by [`ml_type`], which arguments can themselves be
+///   patterns.
+///
+/// Arms can have guards. They are tried in order, and the first one which
+/// pattern matches and which guard is true is selected. The macro panics if no
+/// arm matches. Values which are not valid for their type (see [`Invalid`]) are
+/// only matched by wildcards and identifiers.
+///
+/// The matched value, as well as all the references bound by the patterns,
+/// share the lifetime of the input reference.
+///
+/// The arms are expanded inside labeled blocks, so their bodies can only
+/// `break` or `continue` an enclosing loop through its label:
+///
+/// ```
+/// 'lists: for l in lists {
+///     ml_match! { l;
+///         list![] => continue 'lists,
+///         list![hd :: _] if int::from(hd) < int::from(0) => break 'lists,
+///         list![hd :: _] => sum += isize::from(int::from(hd)),
+///     }
+/// }
+/// ```
+///
+/// An unlabeled `break` or `continue` is rejected with error E0695:
+///
+/// ```compile_fail,E0695
+/// for l in lists {
+///     ml_match! { l;
+///         list![] => continue,
+///         _ => (),
+///     }
+/// }
+/// ```
+///
+/// Note: for matcher macros to be usable in nested patterns, they must handle
+/// a call of the form `m!(@ml_match 'label value { continuation } args...)`,
+/// typically by forwarding it to the `@inline` or `@block` rules of this
+/// macro, as is done by the `list` macro.
+// The patterns of each arm are compiled in continuation-passing style: each
+// sub-pattern is matched, and the continuation is expanded in the scope of the
+// bindings it introduces. The continuation of the last sub-pattern evaluates
+// the guard and breaks out of the whole macro with the value of the arm. A
+// failed match breaks out of the block of the arm to try the next one.
+#[macro_export] macro_rules! ml_match {
+    ( $e:expr ; $($arms:tt)+ ) => {{
+        let value = $e;
+        #[allow(unused_labels, unreachable_code)]
+        let result = 'done: {
+            ml_match!(@arms value 'done [] $($arms)+);
+            panic!("no pattern matched the value in ml_match!")
+        };
+        result
+    }};
+
+    // Parsing of the arms.
+    (@arms $v:ident $done:lifetime [$($p:tt)*] if $guard:expr => $($rest:tt)+) => {
+        ml_match!(@body $v $done [$($p)*] [$guard] $($rest)+);
+    };
+    (@arms $v:ident $done:lifetime [$($p:tt)*] => $($rest:tt)+) => {
+        ml_match!(@body $v $done [$($p)*] [true] $($rest)+);
+    };
+    (@arms $v:ident $done:lifetime [$($p:tt)*] $t:tt $($rest:tt)*) => {
+        ml_match!(@arms $v $done [$($p)* $t] $($rest)*);
+    };
+    (@arms $v:ident $done:lifetime []) => {};
+
+    (@body $v:ident $done:lifetime $p:tt $g:tt $body:expr , $($rest:tt)*) => {
+        ml_match!(@arm $v $done $p $g $body);
+        ml_match!(@arms $v $done [] $($rest)*);
+    };
+    (@body $v:ident $done:lifetime $p:tt $g:tt $body:expr) => {
+        ml_match!(@arm $v $done $p $g $body);
+    };
+    (@body $v:ident $done:lifetime $p:tt $g:tt $body:block $($rest:tt)*) => {
+        ml_match!(@arm $v $done $p $g $body);
+        ml_match!(@arms $v $done [] $($rest)*);
+    };
+
+    (@arm $v:ident $done:lifetime [$($p:tt)*] [$guard:expr] $body:expr) => {
+        'arm: {
+            ml_match!(@pat 'arm $v { if $guard { break $done ($body) } } $($p)*);
+        }
+    };
+
+    // Compilation of a pattern.
+    (@pat $fail:lifetime $v:ident $k:tt _) => { $k };
+    (@pat $fail:lifetime $v:ident $k:tt None) => {
+        ml_match!(@inline $fail $v $k [$crate::stdlib::option::tag::Inline::None])
+    };
+    (@pat $fail:lifetime $v:ident $k:tt Some ( $($p:tt)+ )) => {
+        ml_match!(@block $fail $v $k [$crate::stdlib::option::View::Some] $($p)+)
+    };
+    (@pat $fail:lifetime $v:ident $k:tt $m:ident ! [ $($p:tt)* ]) => {
+        $m!(@ml_match $fail $v $k $($p)*)
+    };
+    (@pat $fail:lifetime $v:ident $k:tt $m:ident ! ( $($p:tt)* )) => {
+        $m!(@ml_match $fail $v $k $($p)*)
+    };
+    (@pat $fail:lifetime $v:ident $k:tt ( $($p:tt)+ )) => {
+        ml_match!(@pat $fail $v $k $($p)+)
+    };
+    (@pat $fail:lifetime $v:ident $k:tt $x:ident) => {{
+        ml_match!(@binding $x);
+        let $x = $v;
+        $k
+    }};
+
+    // Rejects capitalized bindings, which are most likely constant
+    // constructors missing their matcher macro and would match any value.
+    (@binding $x:ident) => {
+        const _: () = if stringify!($x).as_bytes()[0].is_ascii_uppercase() {
+            panic!(concat!("`", stringify!($x), "` would bind any value in ml_match!, ",
+                           "constant constructors must be matched as `m![",
+                           stringify!($x), "]` where `m` is the matcher macro of their type"))
+        };
+    };
+
+    // Matches `$v` against a constant constructor which tag is `$tag`.
+    (@inline $fail:lifetime $v:ident $k:tt [$($tag:tt)*]) => {
+        match $crate::matching::match_($v) {
+            ::std::result::Result::Ok($crate::matching::Matcher::Inline($($tag)*)) => $k,
+            #[allow(unreachable_patterns)]
+            _ => break $fail
+        }
+    };
+
+    // Matches `$v` against a non-constant constructor which view is `$ctor`,
+    // and its arguments against the comma-separated patterns.
+    (@block $fail:lifetime $v:ident $k:tt $ctor:tt $($p:tt)+) => {
+        ml_match!(@split $fail $v $k $ctor [] [] $($p)+)
+    };
+    (@split $fail:lifetime $v:ident $k:tt $ctor:tt [$($ps:tt)*] [$($p:tt)*]
+            , $($rest:tt)+) => {
+        ml_match!(@split $fail $v $k $ctor [$($ps)* [$($p)*]] [] $($rest)+)
+    };
+    (@split $fail:lifetime $v:ident $k:tt $ctor:tt $ps:tt [$($p:tt)*]
+            $t:tt $($rest:tt)*) => {
+        ml_match!(@split $fail $v $k $ctor $ps [$($p)* $t] $($rest)*)
+    };
+    (@split $fail:lifetime $v:ident $k:tt $ctor:tt [$($ps:tt)*] [$($p:tt)*]) => {
+        ml_match!(@zip $fail $v $k $ctor [] [$($ps)* [$($p)*]]
+                  [x0 x1 x2 x3 x4 x5 x6 x7 x8 x9 x10 x11])
+    };
+    (@zip $fail:lifetime $v:ident $k:tt $ctor:tt [$($acc:tt)*] [$p:tt $($ps:tt)*]
+          [$x:ident $($xs:ident)*]) => {
+        ml_match!(@zip $fail $v $k $ctor [$($acc)* ($x $p)] [$($ps)*] [$($xs)*])
+    };
+    (@zip $fail:lifetime $v:ident $k:tt [$($ctor:tt)*] [$( ($x:ident $p:tt) )*] []
+          $xs:tt) => {
+        match $crate::matching::match_($v) {
+            ::std::result::Result::Ok($crate::matching::Matcher::Block(
+                $($ctor)*(&ml_block!(pat $($x),*))
+            )) => {
+                ml_match!(@fields $fail $k $( ($x $p) )*)
+            }
+            #[allow(unreachable_patterns)]
+            _ => break $fail
+        }
+    };
+    (@fields $fail:lifetime $k:tt ($x:ident [$($p:tt)*]) $($rest:tt)*) => {
+        ml_match!(@pat $fail $x { ml_match!(@fields $fail $k $($rest)*) } $($p)*)
+    };
+    (@fields $fail:lifetime $k:tt) => { $k };
 }
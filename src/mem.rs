@@ -1,7 +1,14 @@
-use std::cell::UnsafeCell;
+use std::cell::{RefCell, UnsafeCell};
 #[macro_use] use raw;
+use stdlib::exn::Exn;
+use stdlib::string::MlString;
 
 /// The trait of types that can build into an ML value.
+///
+/// # Safety
+///
+/// The value returned by `build` must be a valid value of type `Result`, as it
+/// is then stored as such in the OCaml heap.
 pub unsafe trait Build {
     type Result;
 
@@ -11,7 +18,7 @@ pub unsafe trait Build {
     ///
     /// The pointer in `at` should have already been registered before
     /// calling this function on it.
-    fn build<'a>(self, &'a mut Gc) -> &'a Self::Result;
+    fn build(self, gc: &mut Gc) -> &Self::Result;
 }
 
 /// The Garbage-Collector interface
@@ -26,10 +33,10 @@ pub unsafe trait Build {
 /// peformed. This ensures that:
 ///
 /// * The OCaml GC won't release memory that is still accessible by the Rust
-/// code (which would cause memory corruption).
+///   code (which would cause memory corruption).
 ///
 /// * Such values will be freed once they're not accessible by the Rust code
-/// anymore (unless they are from OCaml code).
+///   anymore (unless they are from OCaml code).
 ///
 /// This is done by having all memory-sensible operations go through the [`Gc`]
 /// type which will be borrowed whenever unregistered values exist, preventing
@@ -38,18 +45,235 @@ pub unsafe trait Build {
 /// Sensible memory-operations mainly include heap-allocating values, which is
 /// performed by the [`alloc`] and [`raw_alloc`] methods, but will mainly be
 /// done more transparently through the [`alloc`] macro.
-// NOTE: This is a tupe-struct around unit just to make it an abstract type and
-// prevent new values from being called without using the unsafe `new` method.
-pub struct Gc(());
+///
+/// The [`Gc`] also allows raising OCaml exceptions, with [`raise`] and the
+/// related methods. Raising an exception doesn't run the destructors of the
+/// Rust frames between the raise and the OCaml handler, which must therefore
+/// not own any resource. The roots registered since the creation of the [`Gc`]
+/// are however properly unregistered.
+///
+/// The root chain is also saved around the calls to OCaml closures, and
+/// restored when the [`Gc`] is dropped, so that it remains consistent when
+/// OCaml and Rust code call each other recursively, whatever the exceptions
+/// raised in between.
+// NOTE: The fields are private to make it an abstract type and prevent new
+// values from being created without using the unsafe `new` method.
+pub struct Gc {
+    // The root chain when this Gc was created, which is restored before raising
+    // an exception, as the destructors of the roots won't run.
+    roots: *mut CamlRootsBlock,
+    // The number of Gcs live when this one was created, that is, its position
+    // in ENTRY_ROOTS.
+    depth: usize
+}
+
+thread_local! {
+    // The root chains when the live Gcs were created, the innermost last. They
+    // are restored by the exceptions raised without a Gc at hand, such as
+    // `Division_by_zero`, which also leave the innermost Gc for good.
+    static ENTRY_ROOTS: RefCell<Vec<*mut CamlRootsBlock>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Unregisters all the roots registered since the creation of the innermost
+/// [`Gc`], which is left for good.
+///
+/// This function should probably not be used directly, but must be called
+/// before raising an exception through the raw API.
+///
+/// # Safety
+///
+/// The roots registered since the creation of the innermost [`Gc`] must not be
+/// used afterwards, nor that `Gc` itself.
+pub unsafe fn unwind_roots() {
+    if let Some(roots) = ENTRY_ROOTS.with(|entries| entries.borrow_mut().pop()) {
+        caml_local_roots = roots;
+    }
+}
 
 impl Gc {
+    /// Creates the interface to the GC.
+    ///
+    /// This function should probably not be used directly, but is used by the
+    /// [`ml_extern`] macro.
+    ///
+    /// # Safety
+    ///
+    /// This must be called before registering any root, and the resulting
+    /// value must not outlive the roots registered before.
     pub unsafe fn new() -> Gc {
-        Gc(())
+        let roots = caml_local_roots;
+        let depth = ENTRY_ROOTS.with(|entries| {
+            let mut entries = entries.borrow_mut();
+            entries.push(roots);
+            entries.len() - 1
+        });
+        Gc { roots, depth }
+    }
+
+    /// Calls into OCaml code, such as a closure.
+    ///
+    /// The root chain is restored once the call returns, as the Rust code
+    /// called back in between might have exited through an exception without
+    /// unregistering its roots.
+    ///
+    /// This function should probably not be used directly, but is used by the
+    /// closure bindings.
+    ///
+    /// # Safety
+    ///
+    /// `f` may trigger a collection: the values used afterwards must have been
+    /// registered as roots.
+    pub unsafe fn callback<F: FnOnce() -> raw::Value>(&mut self, f: F) -> raw::Value {
+        let roots = caml_local_roots;
+        let depth = ENTRY_ROOTS.with(|entries| entries.borrow().len());
+        let res = f();
+        caml_local_roots = roots;
+        ENTRY_ROOTS.with(|entries| entries.borrow_mut().truncate(depth));
+        res
+    }
+
+    /// Raises an OCaml exception.
+    pub fn raise<B: Build<Result = Exn>>(&mut self, exn: B) -> ! {
+        let exn = exn.build(self);
+        unsafe {
+            let exn = ::std::mem::transmute::<&Exn, raw::Value>(exn);
+            self.unwind_roots();
+            raw::raise(exn)
+        }
+    }
+
+    /// Raises an exception without argument.
+    ///
+    /// Such exceptions are represented by their constructor, which can be
+    /// obtained with [`Exn::named`].
+    pub fn raise_constant(&self, tag: &Exn) -> ! {
+        unsafe {
+            let tag = ::std::mem::transmute::<&Exn, raw::Value>(tag);
+            self.unwind_roots();
+            raw::raise(tag)
+        }
+    }
+
+    /// Raises an exception with an argument, such as `My_exn of string`.
+    ///
+    /// The constructor can be obtained with [`Exn::named`], after the argument
+    /// has been rooted.
+    pub fn raise_with_arg<T>(&self, tag: &Exn, arg: &T) -> ! {
+        unsafe {
+            let tag = ::std::mem::transmute::<&Exn, raw::Value>(tag);
+            let arg = ::std::mem::transmute::<&T, raw::Value>(arg);
+            self.unwind_roots();
+            raw::raise_with_arg(tag, arg)
+        }
+    }
+
+    /// Raises the `Failure` exception with the given message.
+    ///
+    /// The message may be an owned `String`, which is then released before the
+    /// exception is raised.
+    pub fn failwith<B: Build<Result = MlString>>(&mut self, msg: B) -> ! {
+        let msg = msg.build(self);
+        unsafe {
+            let msg = ::std::mem::transmute::<&MlString, raw::Value>(msg);
+            self.unwind_roots();
+            raw::failwith_value(msg)
+        }
+    }
+
+    /// Raises the `Invalid_argument` exception with the given message.
+    pub fn invalid_arg<B: Build<Result = MlString>>(&mut self, msg: B) -> ! {
+        let msg = msg.build(self);
+        unsafe {
+            let msg = ::std::mem::transmute::<&MlString, raw::Value>(msg);
+            self.unwind_roots();
+            raw::invalid_argument_value(msg)
+        }
     }
 
+    /// Raises the `Not_found` exception.
+    pub fn raise_not_found(&self) -> ! {
+        unsafe {
+            self.unwind_roots();
+            raw::raise_not_found()
+        }
+    }
+
+    // Unregisters all the roots registered since the creation of this Gc, which
+    // is left for good.
+    unsafe fn unwind_roots(&self) {
+        caml_local_roots = self.roots;
+        ENTRY_ROOTS.with(|entries| entries.borrow_mut().truncate(self.depth));
+    }
+
+    /// Allocates a block with the given tag and fields.
+    ///
+    /// # Safety
+    ///
+    /// The block must be a valid value of type `T`, and the values used
+    /// afterwards must have been registered as roots.
     pub unsafe fn raw_alloc<'a, T>(&self, tag: u8, fields: &[raw::Value]) -> &'a T {
         ::std::mem::transmute(raw::alloc(tag, fields))
     }
+
+    /// Builds a chain of blocks linked through one of their fields, such as a
+    /// list, iteratively.
+    ///
+    /// Each of the `cells` is built into a block, which field `link` is then
+    /// set to point to the next one, the last one pointing to `end`. This only
+    /// uses a constant amount of stack and of roots, whatever the number of
+    /// cells:
+    ///
+    /// ```
+    /// let lst = unsafe { gc.chain(1, (0..1000000).map(|i| Cons(int::from(i), Nil())), Nil()) };
+    /// ```
+    ///
+    /// This function should probably not be used directly: [`list::from_iter`]
+    /// and the `chain` functions generated by [`ml_type`] wrap it safely.
+    ///
+    /// # Safety
+    ///
+    /// The field `link` of each cell must hold an immediate value once built,
+    /// and must be of the type of the whole chain.
+    pub unsafe fn chain<I, E>(&mut self, link: usize, cells: I, end: E) -> &E::Result
+        where I: Iterator,
+              I::Item: Build<Result = E::Result>,
+              E: Build {
+        // The `local` macro isn't available yet at this point.
+        let first: P<E::Result> = P::new();
+        first.register();
+        let last: P<E::Result> = P::new();
+        last.register();
+
+        let mut empty = true;
+        for cell in cells {
+            let cell = cell.build(self);
+            if empty {
+                first.root(cell);
+            } else {
+                let fld = raw::field(last.value(), link);
+                raw::initialize(fld, ::std::mem::transmute::<&E::Result, raw::Value>(cell));
+            }
+            last.root(cell);
+            empty = false;
+        }
+
+        let end = end.build(self);
+        if empty {
+            return end;
+        }
+
+        let end = ::std::mem::transmute::<&E::Result, raw::Value>(end);
+        raw::initialize(raw::field(last.value(), link), end);
+        ::std::mem::transmute(first.value())
+    }
+}
+
+// The roots are normally unregistered by their own destructors by now, which
+// this only enforces.
+impl Drop for Gc {
+    fn drop(&mut self) {
+        unsafe { self.unwind_roots() }
+    }
 }
 
 #[macro_export] macro_rules! alloc {
@@ -120,13 +344,14 @@ impl Drop for CamlRootsBlock {
 impl<'a, T: 'a> P<'a, T> {
     /// Create a new, uninitialized pointer.
     ///
-    /// This function is unsafe as passing the resulting value to code expecting
-    /// a valid pointer will fail. Any attempt to intialize this pointer before
-    /// it's registered might cause the GC to release the still-accessible
-    /// pointed memory.
-    ///
     /// This function should probably not be used directly, but rather through
     /// the [`local`] macro.
+    ///
+    /// # Safety
+    ///
+    /// Passing the resulting value to code expecting a valid pointer will fail.
+    /// Any attempt to intialize this pointer before it's registered might cause
+    /// the GC to release the still-accessible pointed memory.
     // NOTE: This value is initialized with an OCaml value representing the
     // integer 1. Because of that, any use of that value will cause undefined
     // behaviour but the GC will be able to safely browse it, if a cycle occurs
@@ -150,11 +375,13 @@ impl<'a, T: 'a> P<'a, T> {
     /// the value ‶locked″ in place, preventing it from being moved or copied
     /// without explicit cloning.
     ///
-    /// This function is unsafe because attempting to register a single pointer
-    /// multiple times might cause leakage of unaccessible memory.
-    ///
     /// This function should probably not be used directly, but rather through
     /// the [`local`] macro.
+    ///
+    /// # Safety
+    ///
+    /// Attempting to register a single pointer multiple times might cause
+    /// leakage of unaccessible memory.
     pub unsafe fn register(&'a self) {
         // debug!("registering location {:?} as GC root {:?}",
         //        self.val.get(),
@@ -174,20 +401,22 @@ impl<'a, T: 'a> P<'a, T> {
     ///
     /// This function should probably not be used directly, but rather through
     /// the [`local`] macro.
-    pub fn root<'b>(&self, val: &'b T) {
-        unsafe { *self.val.get() = ::std::mem::transmute(val) }
+    pub fn root(&self, val: &T) {
+        unsafe { *self.val.get() = ::std::mem::transmute::<&T, raw::Value>(val) }
     }
 
     /// Creates a new pointer from a raw underlying ML value.
     ///
+    /// This function should probably not be used directly, but is used
+    /// internally by the [`ml_extern`] macro.
+    ///
+    /// # Safety
+    ///
     /// This is extremely unsafe as there is no guarantee that the input ML
     /// value is a valid pointer referencing valid data of the correct type.
     ///
     /// The resulting pointer value must then be registered before the GC can be
     /// used again.
-    ///
-    /// This function should probably not be used directly, but is used
-    /// internally by the [`ml_extern`] macro.
     pub unsafe fn from(val: raw::Value) -> P<'a, T> {
         let ptr = P::new();
         *ptr.val.get() = val;
@@ -198,6 +427,11 @@ impl<'a, T: 'a> P<'a, T> {
     ///
     /// This function should probably not be used directly, but might be used
     /// internally by some macros or functions.
+    ///
+    /// # Safety
+    ///
+    /// The result is not rooted: it must not be used after the next
+    /// allocation, unless it is an immediate value.
     pub unsafe fn value(&self) -> raw::Value {
         *self.val.get()
     }
@@ -218,7 +452,7 @@ impl<'a, T> ::std::convert::AsRef<T> for P<'a, T> {
 }
 
 // todo: remove that! this is only safe for ml values. there should be a trait bound.
-unsafe impl<'a, T> Build for &'a T {
+unsafe impl<T> Build for &T {
     type Result = T;
     fn build<'b>(self, gc: &mut Gc) -> &T {
         unsafe { ::std::mem::transmute(self) }
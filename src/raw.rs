@@ -38,6 +38,381 @@ pub struct Value(pub isize);
     ( $v:expr ) => ( ($v) >> 1 )
 );
 
+/// Tag of the blocks holding OCaml strings and bytes.
+pub const STRING_TAG: u8 = 252;
+
+/// Tag of the blocks holding boxed floats.
+pub const DOUBLE_TAG: u8 = 253;
+
+/// Tag of the blocks holding flat float arrays.
+pub const DOUBLE_ARRAY_TAG: u8 = 254;
+
+/// Reads the header of a block
+///
+/// The header contains the size of the block in words, as well as its tag,
+/// which can be extracted with [`wosize`] and [`tag`].
+///
+/// # Safety
+///
+/// `blk` must be a valid block.
+pub unsafe fn header(blk: Value) -> usize {
+    let Value(ptr) = blk;
+    *(ptr as *const usize).offset(-1)
+}
+
+/// Extracts the size of a block, in words, from its header.
+pub fn wosize(hdr: usize) -> usize {
+    hdr >> 10
+}
+
+/// Extracts the tag of a block from its header.
+pub fn tag(hdr: usize) -> u8 {
+    (hdr & 0xff) as u8
+}
+
+/// Returns the length, in bytes, of a string or bytes block
+///
+/// # Safety
+///
+/// `blk` must be a valid block of tag [`STRING_TAG`].
+pub unsafe fn string_length(blk: Value) -> usize {
+    // The last byte of the block is a padding byte containing the number of
+    // padding bytes before it.
+    let Value(ptr) = blk;
+    let bsize = wosize(header(blk)) * ::std::mem::size_of::<Value>();
+    bsize - 1 - *(ptr as *const u8).add(bsize - 1) as usize
+}
+
+/// Allocates a string or bytes block of the given length
+///
+/// The content of the block is left uninitialized.
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn alloc_string(len: usize) -> Value {
+    extern "C" {
+        fn caml_alloc_string(len: usize) -> Value;
+    }
+
+    caml_alloc_string(len)
+}
+
+/// Allocates a boxed float
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn copy_double(f: f64) -> Value {
+    extern "C" {
+        fn caml_copy_double(f: f64) -> Value;
+    }
+
+    caml_copy_double(f)
+}
+
+/// Allocates a boxed 32-bits integer
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn copy_int32(i: i32) -> Value {
+    extern "C" {
+        fn caml_copy_int32(i: i32) -> Value;
+    }
+
+    caml_copy_int32(i)
+}
+
+/// Allocates a boxed 64-bits integer
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn copy_int64(i: i64) -> Value {
+    extern "C" {
+        fn caml_copy_int64(i: i64) -> Value;
+    }
+
+    caml_copy_int64(i)
+}
+
+/// Allocates a boxed native integer
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn copy_nativeint(i: isize) -> Value {
+    extern "C" {
+        fn caml_copy_nativeint(i: isize) -> Value;
+    }
+
+    caml_copy_nativeint(i)
+}
+
+/// Returns a pointer to the data of a custom block
+///
+/// The first field of a custom block points to its custom operations, its data
+/// is stored right after.
+///
+/// # Safety
+///
+/// `blk` must be a valid custom block.
+pub unsafe fn custom_data(blk: Value) -> *mut u8 {
+    field(blk, 1) as *mut u8
+}
+
+/// Allocates a float array of the given length
+///
+/// Depending on how the runtime was configured, the result is either a flat
+/// block of tag [`DOUBLE_ARRAY_TAG`], which content is left uninitialized, or a
+/// regular block which fields are initialized to `()`.
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn alloc_float_array(len: usize) -> Value {
+    extern "C" {
+        fn caml_alloc_float_array(len: usize) -> Value;
+    }
+
+    caml_alloc_float_array(len)
+}
+
+/// Raises the `Division_by_zero` exception
+///
+/// This doesn't return, but unwinds the stack up to the nearest OCaml exception
+/// handler without running the destructors of the Rust frames in between. It
+/// must therefore only be called from code called by OCaml.
+///
+/// # Safety
+///
+/// This must only be called from code called by OCaml, after the roots
+/// registered by the Rust frames in between have been unwound.
+pub unsafe fn raise_zero_divide() -> ! {
+    extern "C" {
+        fn caml_raise_zero_divide() -> !;
+    }
+
+    caml_raise_zero_divide()
+}
+
+/// Structural comparison, `compare` in OCaml
+///
+/// Returns a negative integer, zero, or a positive integer, as an ML value. This
+/// raises `Invalid_argument` on functional values.
+///
+/// # Safety
+///
+/// `v1` and `v2` must be valid values. The comparison raises on functional
+/// values, so the same requirements as for [`raise`] apply unless they are
+/// known not to contain any.
+pub unsafe fn compare(v1: Value, v2: Value) -> Value {
+    extern "C" {
+        fn caml_compare(v1: Value, v2: Value) -> Value;
+    }
+
+    caml_compare(v1, v2)
+}
+
+/// Raises an exception
+///
+/// Just like [`raise_zero_divide`], this unwinds the stack without running the
+/// destructors of the Rust frames, and must only be called from code called by
+/// OCaml.
+///
+/// # Safety
+///
+/// This must only be called from code called by OCaml, after the roots
+/// registered by the Rust frames in between have been unwound. `exn` must be a
+/// valid exception.
+pub unsafe fn raise(exn: Value) -> ! {
+    extern "C" {
+        fn caml_raise(exn: Value) -> !;
+    }
+
+    caml_raise(exn)
+}
+
+/// Raises an exception with an argument
+///
+/// `tag` is the constructor of the exception, and `arg` its argument.
+///
+/// # Safety
+///
+/// This must only be called from code called by OCaml, after the roots
+/// registered by the Rust frames in between have been unwound. `tag` must be a
+/// valid exception constructor.
+pub unsafe fn raise_with_arg(tag: Value, arg: Value) -> ! {
+    extern "C" {
+        fn caml_raise_with_arg(tag: Value, arg: Value) -> !;
+    }
+
+    caml_raise_with_arg(tag, arg)
+}
+
+/// Raises the `Not_found` exception
+///
+/// # Safety
+///
+/// This must only be called from code called by OCaml, after the roots
+/// registered by the Rust frames in between have been unwound.
+pub unsafe fn raise_not_found() -> ! {
+    extern "C" {
+        fn caml_raise_not_found() -> !;
+    }
+
+    caml_raise_not_found()
+}
+
+/// Raises the `Failure` exception with the given OCaml string
+///
+/// # Safety
+///
+/// This must only be called from code called by OCaml, after the roots
+/// registered by the Rust frames in between have been unwound. `msg` must be an
+/// OCaml string.
+pub unsafe fn failwith_value(msg: Value) -> ! {
+    extern "C" {
+        fn caml_failwith_value(msg: Value) -> !;
+    }
+
+    caml_failwith_value(msg)
+}
+
+/// Raises the `Invalid_argument` exception with the given OCaml string
+///
+/// # Safety
+///
+/// This must only be called from code called by OCaml, after the roots
+/// registered by the Rust frames in between have been unwound. `msg` must be an
+/// OCaml string.
+pub unsafe fn invalid_argument_value(msg: Value) -> ! {
+    extern "C" {
+        fn caml_invalid_argument_value(msg: Value) -> !;
+    }
+
+    caml_invalid_argument_value(msg)
+}
+
+/// Applies a closure to an argument
+///
+/// If the closure raises an exception, it is caught and returned as an
+/// exception result, see [`is_exception_result`].
+///
+/// # Safety
+///
+/// `closure` must be an OCaml closure taking at least one argument. The closure
+/// may trigger a collection, hence the caller must have registered as roots all
+/// the values it still uses afterwards.
+pub unsafe fn callback_exn(closure: Value, arg: Value) -> Value {
+    extern "C" {
+        fn caml_callback_exn(closure: Value, arg: Value) -> Value;
+    }
+
+    caml_callback_exn(closure, arg)
+}
+
+/// Applies a closure to several arguments
+///
+/// Just like [`callback_exn`], exceptions are returned as exception results.
+///
+/// # Safety
+///
+/// `closure` must be an OCaml closure taking at least `args.len()` arguments.
+/// Just like for [`callback_exn`], the values still used afterwards must be
+/// registered as roots.
+pub unsafe fn callbackn_exn(closure: Value, args: &[Value]) -> Value {
+    extern "C" {
+        fn caml_callbackN_exn(closure: Value, narg: i32, args: *const Value) -> Value;
+    }
+
+    caml_callbackN_exn(closure, args.len() as i32, args.as_ptr())
+}
+
+/// Returns whether the result of a callback is an exception
+pub fn is_exception_result(v: Value) -> bool {
+    let Value(raw) = v;
+    raw & 3 == 2
+}
+
+/// Extracts the exception from the result of a callback
+pub fn extract_exception(v: Value) -> Value {
+    let Value(raw) = v;
+    Value(raw & !3)
+}
+
+/// Returns the location of a value registered with `Callback.register`
+///
+/// The result is null if no value was registered under this name.
+///
+/// # Safety
+///
+/// `name` must point to a nul-terminated string.
+pub unsafe fn named_value(name: *const u8) -> *const Value {
+    extern "C" {
+        fn caml_named_value(name: *const u8) -> *const Value;
+    }
+
+    caml_named_value(name)
+}
+
+/// Raw field access
+///
+/// Returns a pointer to the field `i` of the block `blk`. No check is performed
+/// on whether `blk` actually is a block, nor on whether it has at least `i + 1`
+/// fields.
+///
+/// # Safety
+///
+/// `blk` must be a valid block, and `i` must be lower than its size.
+pub unsafe fn field(blk: Value, i: usize) -> *mut Value {
+    let Value(ptr) = blk;
+    (ptr as *mut Value).add(i)
+}
+
+/// Raw write barrier
+///
+/// Stores `value` in the field pointed to by `fld`, which is expected to be
+/// the field of a block already initialized, as returned by [`field`]. This
+/// must be used instead of a plain assignment for the GC to be notified of
+/// pointers from the major heap to the minor heap.
+///
+/// # Safety
+///
+/// `fld` must point to the field of an initialized block.
+pub unsafe fn modify(fld: *mut Value, value: Value) {
+    extern "C" {
+        fn caml_modify(fld: *mut Value, value: Value);
+    }
+
+    caml_modify(fld, value)
+}
+
+/// Raw field initialization
+///
+/// Stores `value` in the field pointed to by `fld`, notifying the GC if needed.
+/// Unlike [`modify`], this may only be used on fields which don't hold a
+/// pointer yet: either fields of freshly allocated blocks, or fields holding
+/// an immediate value.
+///
+/// # Safety
+///
+/// `fld` must point to a field which doesn't hold a pointer yet.
+pub unsafe fn initialize(fld: *mut Value, value: Value) {
+    extern "C" {
+        fn caml_initialize(fld: *mut Value, value: Value);
+    }
+
+    caml_initialize(fld, value)
+}
+
 /// Raw allocation primitive
 ///
 /// This is the direct (unsafe) interface to the Garbage-Collector. `alloc(tag,
@@ -57,18 +432,21 @@ pub struct Value(pub isize);
 ///
 /// * [The OCaml manual, chapter 20: Interfacing with C]
 /// * [Real World OCaml]
+///
+/// # Safety
+///
+/// This may trigger a collection: the caller must have registered as roots all
+/// the values it still uses afterwards.
 pub unsafe fn alloc(tag: u8, values: &[Value]) -> Value {
     extern "C" {
         fn caml_alloc(size: usize, tag: u8) -> *mut Value;
-        fn caml_modify(tgt: *mut Value, value: Value);
     }
 
     unsafe {
-        println!("caml_alloc({}, {})", values.len(), tag);
         let blk = caml_alloc(values.len(), tag);
 
         for (i, val) in values.iter().enumerate() {
-            caml_modify(blk.offset(i as isize), *val);
+            modify(blk.add(i), *val);
         }
 
         Value(blk as isize)
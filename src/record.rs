@@ -0,0 +1,140 @@
+//! Access to the fields of OCaml records
+//!
+//! OCaml records are represented as blocks of tag 0, which fields are stored
+//! in the order of the type definition. Record bindings are typically declared
+//! with the [`ml_type`] macro, which generates for each record type:
+//!
+//! * A marker type for each of its fields, in a `field` module, implementing
+//!   [`Field`] (and [`Mutable`] for the fields declared `mutable`).
+//! * A getter method for each field, named after it.
+//! * A builder in the `build` module, with the same fields as the record.
+//! * The implementation of the [`Record`] trait, which provides a write-barrier
+//!   correct setter for mutable fields and functional updates.
+//!
+//! ```
+//! ml_type! {
+//!     type point = { x : int; mutable y : int };
+//! }
+//!
+//! local! {
+//!     let p = alloc!(gc: build::point { x: int::from(1), y: int::from(2) });
+//!     let q = alloc!(gc: p.as_ref().with(field::x, int::from(3)));
+//! }
+//!
+//! p.as_ref().set(field::y, q.as_ref().x());
+//! ```
+//!
+//! Note: records which fields are all of type `float` have a different
+//! representation in OCaml: `ml_type!` refuses to bind them.
+//!
+//! ```compile_fail
+//! ml_type! {
+//!     type vec2 = { x : float; y : float };
+//! }
+//! ```
+
+#[macro_use] use raw;
+use mem::{Build, Gc, P};
+
+/// A field of the OCaml record type `R`.
+///
+/// # Safety
+///
+/// Implementing this trait with the wrong index or type would make the field
+/// accessors read values of the wrong type.
+pub unsafe trait Field<R> {
+    /// The type of the values stored in this field.
+    type Type;
+
+    /// The index of this field in the block representing the record.
+    const INDEX: usize;
+}
+
+/// A field of the OCaml record type `R` declared as `mutable`.
+///
+/// # Safety
+///
+/// This trait must only be implemented for fields declared as `mutable`, as
+/// other fields may be shared by the OCaml compiler.
+pub unsafe trait Mutable<R>: Field<R> {}
+
+/// The trait of bindings to OCaml record types.
+///
+/// # Safety
+///
+/// Implementing this trait for a type which values are not blocks of `SIZE`
+/// fields will make its methods access invalid memory.
+pub unsafe trait Record: Sized {
+    /// The number of fields of this record type.
+    const SIZE: usize;
+
+    /// Returns the value stored in the given field of this record.
+    fn get<F: Field<Self>>(&self, field: F) -> &F::Type {
+        unsafe {
+            let blk = ::std::mem::transmute::<&Self, raw::Value>(self);
+            ::std::mem::transmute(*raw::field(blk, F::INDEX))
+        }
+    }
+
+    /// Stores a new value in the given mutable field of this record.
+    ///
+    /// This performs the write barrier required by the GC, and is therefore
+    /// safe to use on records from any part of the OCaml heap.
+    fn set<F: Mutable<Self>>(&self, field: F, value: &F::Type) {
+        unsafe {
+            let blk = ::std::mem::transmute::<&Self, raw::Value>(self);
+            let value = ::std::mem::transmute::<&F::Type, raw::Value>(value);
+            raw::modify(raw::field(blk, F::INDEX), value)
+        }
+    }
+
+    /// Returns a lazy builder for a copy of this record, where the given field
+    /// holds the value built from `value`.
+    ///
+    /// This is the equivalent of OCaml's `{ r with field = value }`. Several
+    /// fields can be updated by chaining calls to `with`.
+    fn with<F, V>(&self, field: F, value: V) -> With<&Self, F, V>
+        where F: Field<Self>, V: Build<Result = F::Type> {
+        With { base: self, field, value }
+    }
+}
+
+/// Lazy builder for functional updates of records
+///
+/// See [`Record::with`]. The `base` of the update can itself be any builder of
+/// a record, which allows updating several fields at once.
+pub struct With<B, F, V> {
+    pub base: B,
+    pub field: F,
+    pub value: V
+}
+
+impl<B, F, V> With<B, F, V> {
+    /// Updates one more field of the resulting record.
+    pub fn with<G, W>(self, field: G, value: W) -> With<Self, G, W> {
+        With { base: self, field, value }
+    }
+}
+
+unsafe impl<B, F, V> Build for With<B, F, V>
+    where B: Build, B::Result: Record, F: Field<B::Result>, V: Build<Result = F::Type> {
+    type Result = B::Result;
+
+    fn build(self, gc: &mut Gc) -> &B::Result {
+        local! {
+            let base = alloc!(gc: self.base);
+            let value = alloc!(gc: self.value);
+        }
+
+        unsafe {
+            let size = <B::Result as Record>::SIZE;
+            let blk = gc.raw_alloc(0, &vec![val_int!(0); size]);
+            for i in 0 .. size {
+                let v = if i == F::INDEX { value.value() } else { *raw::field(base.value(), i) };
+                raw::modify(raw::field(::std::mem::transmute::<&B::Result, raw::Value>(blk), i), v);
+            }
+
+            blk
+        }
+    }
+}
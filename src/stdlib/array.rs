@@ -0,0 +1,395 @@
+use mem::Gc;
+use raw;
+use stdlib::float::float;
+
+/// Binding to the OCaml 'a array type
+///
+/// Elements can be read with [`get`] or [`iter`], which are bounds-checked, and
+/// replaced with [`set`]. Arrays can be allocated from a `Vec` of builders, or
+/// from any iterator which length is known with [`from_iter`]:
+///
+/// ```
+/// local! {
+///     let arr = alloc!(gc: vec![Some(int::from(1)), None()]);
+///     let squares = alloc!(gc: from_iter((0..10).map(|i| int::from(i * i))));
+/// }
+/// ```
+///
+/// Arrays of floats get a special treatment by OCaml, which usually stores them
+/// as a flat block of unboxed doubles. The elements of such arrays aren't OCaml
+/// values, and can't be referenced: they are read by value with [`get_float`]
+/// and [`floats`], whatever the representation. The content of flat arrays can
+/// also be borrowed as a slice of `f64`, which, just like for [`Bytes`],
+/// requires borrowing the [`Gc`] for the same lifetime:
+///
+/// ```
+/// fn sum(gc: &mut Gc, arr: &Array<float>) -> f64 {
+///     match arr.as_slice(gc) {
+///         Some(s) => s.iter().sum(),
+///         None => ... // The runtime doesn't use flat float arrays
+///     }
+/// }
+/// ```
+///
+/// Float arrays can be allocated from a slice of `f64`, whatever the
+/// representation used by the runtime:
+///
+/// ```
+/// local! {
+///     let arr = alloc!(gc: &[1.0, 2.0, 3.0][..]);
+/// }
+/// ```
+pub struct Array<T>(::std::marker::PhantomData<T>);
+
+impl<T> Array<T> {
+    /// Returns the number of elements of this array.
+    pub fn len(&self) -> usize {
+        unsafe {
+            let hdr = raw::header(::std::mem::transmute::<&Array<T>, raw::Value>(self));
+            if raw::tag(hdr) == raw::DOUBLE_ARRAY_TAG {
+                raw::wosize(hdr) * ::std::mem::size_of::<raw::Value>()
+                    / ::std::mem::size_of::<f64>()
+            } else {
+                raw::wosize(hdr)
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the element at index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds, or if this is a flat float array, which
+    /// elements aren't OCaml values: those must be read with
+    /// [`get_float`] instead.
+    pub fn get(&self, i: usize) -> &T {
+        self.check_index(i);
+        if self.is_double_array() {
+            panic!("flat float arrays must be read with get_float");
+        }
+
+        unsafe {
+            let blk = ::std::mem::transmute::<&Array<T>, raw::Value>(self);
+            ::std::mem::transmute::<raw::Value, &T>(*raw::field(blk, i))
+        }
+    }
+
+    /// Replaces the element at index `i` by `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn set(&self, i: usize, v: &T) {
+        self.check_index(i);
+
+        unsafe {
+            if self.is_double_array() {
+                // Only floats are stored in flat arrays, and those can be read
+                // directly from their reference.
+                let ptr = self as *const Array<T> as *mut f64;
+                *ptr.add(i) = *(v as *const T as *const f64);
+            } else {
+                let blk = ::std::mem::transmute::<&Array<T>, raw::Value>(self);
+                raw::modify(raw::field(blk, i), ::std::mem::transmute::<&T, raw::Value>(v));
+            }
+        }
+    }
+
+    /// Returns an iterator over the elements of this array.
+    ///
+    /// # Panics
+    ///
+    /// Just like [`get`], panics on flat float arrays, which elements can be
+    /// iterated over with [`floats`] instead.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+        if self.is_double_array() {
+            panic!("flat float arrays must be iterated over with floats");
+        }
+
+        Iter { arr: self, pos: 0, len: self.len() }
+    }
+
+    fn check_index(&self, i: usize) {
+        let len = self.len();
+        if i >= len {
+            panic!("index out of bounds: the len is {} but the index is {}", len, i);
+        }
+    }
+
+    fn is_double_array(&self) -> bool {
+        unsafe {
+            let blk = ::std::mem::transmute::<&Array<T>, raw::Value>(self);
+            raw::tag(raw::header(blk)) == raw::DOUBLE_ARRAY_TAG
+        }
+    }
+}
+
+/// Iterator over the elements of an array
+pub struct Iter<'a, T: 'a> {
+    arr: &'a Array<T>,
+    pos: usize,
+    len: usize
+}
+
+impl<'a, T: 'a> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> ::std::option::Option<&'a T> {
+        if self.pos < self.len {
+            self.pos += 1;
+            ::std::option::Option::Some(self.arr.get(self.pos - 1))
+        } else {
+            ::std::option::Option::None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, ::std::option::Option<usize>) {
+        let rem = self.len - self.pos;
+        (rem, ::std::option::Option::Some(rem))
+    }
+}
+
+impl<'a, T: 'a> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: 'a> IntoIterator for &'a Array<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl Array<float> {
+    /// Returns whether this array is stored as a flat block of doubles.
+    ///
+    /// This is always the case unless the runtime has been configured with
+    /// `-no-flat-float-array`, the empty array excepted.
+    pub fn is_flat(&self) -> bool {
+        self.is_double_array()
+    }
+
+    /// Returns the element at index `i`, whatever the representation of this
+    /// array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn get_float(&self, i: usize) -> float {
+        self.check_index(i);
+
+        unsafe {
+            if self.is_flat() {
+                let ptr = self as *const Array<float> as *const f64;
+                float::from(*ptr.add(i))
+            } else {
+                // Boxed floats can be read directly from their reference.
+                let blk = ::std::mem::transmute::<&Array<float>, raw::Value>(self);
+                *::std::mem::transmute::<raw::Value, &float>(*raw::field(blk, i))
+            }
+        }
+    }
+
+    /// Returns an iterator over the elements of this array, whatever its
+    /// representation.
+    pub fn floats<'a>(&'a self) -> Floats<'a> {
+        Floats { arr: self, pos: 0, len: self.len() }
+    }
+
+    /// Borrows the content of this array, if it is stored flat.
+    ///
+    /// This returns `None` for non-empty arrays of runtimes configured with
+    /// `-no-flat-float-array`, in which floats are individually boxed.
+    pub fn as_slice<'a>(&'a self, gc: &'a Gc) -> Option<&'a [f64]> {
+        if self.is_flat() || self.is_empty() {
+            unsafe {
+                let ptr = self as *const Array<float> as *const f64;
+                Some(::std::slice::from_raw_parts(ptr, self.len()))
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Mutably borrows the content of this array, if it is stored flat.
+    ///
+    /// See [`as_slice`] for when this returns `None`.
+    pub fn as_mut_slice<'a>(&'a self, gc: &'a mut Gc) -> Option<&'a mut [f64]> {
+        if self.is_flat() || self.is_empty() {
+            unsafe {
+                let ptr = self as *const Array<float> as *mut f64;
+                Some(::std::slice::from_raw_parts_mut(ptr, self.len()))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Iterator over the elements of a float array, by value
+pub struct Floats<'a> {
+    arr: &'a Array<float>,
+    pos: usize,
+    len: usize
+}
+
+impl<'a> Iterator for Floats<'a> {
+    type Item = float;
+
+    fn next(&mut self) -> ::std::option::Option<float> {
+        if self.pos < self.len {
+            self.pos += 1;
+            ::std::option::Option::Some(self.arr.get_float(self.pos - 1))
+        } else {
+            ::std::option::Option::None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, ::std::option::Option<usize>) {
+        let rem = self.len - self.pos;
+        (rem, ::std::option::Option::Some(rem))
+    }
+}
+
+impl<'a> ExactSizeIterator for Floats<'a> {}
+
+/// Lazy builders for arrays
+pub mod build {
+    use mem::{Build, Gc, P};
+    use raw;
+    use stdlib::float::float;
+    use super::Array;
+
+    /// Lazy builder for arrays of the elements of an iterator
+    ///
+    /// # Panics
+    ///
+    /// Building panics if the iterator doesn't yield exactly as many elements
+    /// as its `ExactSizeIterator::len()`.
+    pub struct FromIter<I>(pub I);
+
+    unsafe impl<I> Build for FromIter<I>
+        where I: ExactSizeIterator,
+              I::Item: Build {
+        type Result = Array<<I::Item as Build>::Result>;
+
+        fn build(self, gc: &mut Gc) -> &Self::Result {
+            let mut iter = self.0;
+            let len = iter.len();
+
+            // The empty array is the atom of tag 0, which isn't allocated.
+            let hd = match iter.next() {
+                ::std::option::Option::Some(hd) => hd,
+                ::std::option::Option::None => {
+                    check_len(&mut iter, len, 0);
+                    unsafe { return gc.raw_alloc(0, &[]) }
+                }
+            };
+
+            if len == 0 {
+                check_len(&mut iter, len, 1);
+            }
+
+            local! {
+                let first: P<<I::Item as Build>::Result> = alloc!(gc: hd);
+            }
+
+            unsafe {
+                let raw::Value(ptr) = first.value();
+                let is_float = ptr & 1 == 0
+                    && raw::tag(raw::header(first.value())) == raw::DOUBLE_TAG;
+
+                if is_float {
+                    // Just like OCaml does, arrays which elements are floats
+                    // are stored flat, if the runtime supports it.
+                    let mut data = Vec::with_capacity(len);
+                    data.push(*(ptr as *const f64));
+                    for x in iter.by_ref().take(len - 1) {
+                        let x = alloc!(gc: x);
+                        data.push(*(x as *const _ as *const f64));
+                    }
+                    check_len(&mut iter, len, data.len());
+
+                    let arr: &Array<float> = alloc!(gc: &data[..]);
+                    return ::std::mem::transmute::<&Array<float>, &Self::Result>(arr);
+                }
+
+                local! {
+                    let arr: P<Self::Result> = gc.raw_alloc(0, &vec![val_int!(0); len]);
+                }
+
+                // The block is fully initialized already, so bailing out of a
+                // short iterator leaves it in a state the GC can scan.
+                raw::modify(raw::field(arr.value(), 0), first.value());
+                let mut written = 1;
+                for x in iter.by_ref().take(len - 1) {
+                    let x = alloc!(gc: x);
+                    let x = ::std::mem::transmute::<&<I::Item as Build>::Result, raw::Value>(x);
+                    raw::modify(raw::field(arr.value(), written), x);
+                    written += 1;
+                }
+                check_len(&mut iter, len, written);
+
+                ::std::mem::transmute(arr.value())
+            }
+        }
+    }
+
+    /// Panics unless `iter`, of which `written` elements were consumed, had
+    /// exactly `len` elements.
+    fn check_len<I: Iterator>(iter: &mut I, len: usize, written: usize) {
+        if written != len || iter.next().is_some() {
+            panic!("FromIter: the iterator doesn't yield as many elements as its len(), {}",
+                   len);
+        }
+    }
+
+    unsafe impl<B: Build> Build for Vec<B> {
+        type Result = Array<B::Result>;
+
+        fn build(self, gc: &mut Gc) -> &Array<B::Result> {
+            alloc!(gc: FromIter(self.into_iter()))
+        }
+    }
+
+    unsafe impl Build for &[f64] {
+        type Result = Array<float>;
+
+        fn build(self, gc: &mut Gc) -> &Array<float> {
+            unsafe {
+                let blk = raw::alloc_float_array(self.len());
+
+                if self.is_empty() || raw::tag(raw::header(blk)) == raw::DOUBLE_ARRAY_TAG {
+                    let raw::Value(ptr) = blk;
+                    ::std::ptr::copy_nonoverlapping(self.as_ptr(), ptr as *mut f64, self.len());
+                    return ::std::mem::transmute::<raw::Value, &Array<float>>(blk);
+                }
+
+                // The runtime doesn't use flat float arrays: each element has
+                // to be boxed, which requires the array to be rooted.
+                local! {
+                    let arr: P<Array<float>> =
+                        ::std::mem::transmute::<raw::Value, &Array<float>>(blk);
+                }
+
+                for (i, &f) in self.iter().enumerate() {
+                    let f = raw::copy_double(f);
+                    raw::modify(raw::field(arr.value(), i), f);
+                }
+
+                ::std::mem::transmute(arr.value())
+            }
+        }
+    }
+}
+
+/// Creates an array holding the elements of the given iterator.
+pub fn from_iter<I>(iter: I) -> build::FromIter<I::IntoIter>
+    where I: IntoIterator,
+          I::IntoIter: ExactSizeIterator {
+    build::FromIter(iter.into_iter())
+}
@@ -0,0 +1,171 @@
+//! Bindings to the boxed integer types: `int32`, `int64` and `nativeint`.
+//!
+//! Values of those types are custom blocks holding the integer. The binding
+//! types, `int32`, `int64` and `nativeint`, are opaque: the references handed
+//! out by the matching functions point to the custom blocks, and must be
+//! converted to the Rust values `Int32`, `Int64` and `Nativeint` before being
+//! read:
+//!
+//! ```
+//! fn offset<'a>(gc: &'a mut Gc, base: &'a int64, len: &'a int64) -> &'a int64 {
+//!     alloc!(gc: Int64::from(base) + Int64::from(len))
+//! }
+//! ```
+//!
+//! Arithmetic wraps around on overflow, and dividing by zero raises the
+//! `Division_by_zero` exception, as it does in OCaml.
+
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg, Not, BitAnd, BitOr, BitXor, Shl, Shr};
+use mem::{Build, Gc};
+use raw;
+
+macro_rules! boxed_int {
+    ( $(#[$battr:meta])* $binding:ident;
+      $(#[$attr:meta])* $name:ident($int:ident) = $copy:ident ) => {
+        $(#[$battr])*
+        #[allow(non_camel_case_types)]
+        pub struct $binding(::std::marker::PhantomData<$int>);
+
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($int);
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                let &$name(i) = self;
+                write!(fmt, "{}", i)
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                let &$name(i) = self;
+                write!(fmt, "{}", i)
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(i: $int) -> $name {
+                $name(i)
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(i: $name) -> $int {
+                let $name(i) = i;
+                i
+            }
+        }
+
+        // The reference points to the custom block, not to the integer.
+        impl<'a> From<&'a $binding> for $name {
+            fn from(i: &'a $binding) -> $name {
+                unsafe {
+                    let data = raw::custom_data(::std::mem::transmute::<&$binding, raw::Value>(i));
+                    $name(::std::ptr::read(data as *const $int))
+                }
+            }
+        }
+
+        unsafe impl Build for $name {
+            type Result = $binding;
+
+            fn build(self, gc: &mut Gc) -> &$binding {
+                let $name(i) = self;
+                unsafe { ::std::mem::transmute(raw::$copy(i)) }
+            }
+        }
+
+        boxed_int!(@binop $name, Add, add, wrapping_add);
+        boxed_int!(@binop $name, Sub, sub, wrapping_sub);
+        boxed_int!(@binop $name, Mul, mul, wrapping_mul);
+        boxed_int!(@divop $name, Div, div, wrapping_div);
+        boxed_int!(@divop $name, Rem, rem, wrapping_rem);
+        boxed_int!(@binop $name, BitAnd, bitand, bitand);
+        boxed_int!(@binop $name, BitOr, bitor, bitor);
+        boxed_int!(@binop $name, BitXor, bitxor, bitxor);
+
+        impl Neg for $name {
+            type Output = $name;
+            fn neg(self) -> $name {
+                let $name(i) = self;
+                $name(i.wrapping_neg())
+            }
+        }
+
+        impl Not for $name {
+            type Output = $name;
+            fn not(self) -> $name {
+                let $name(i) = self;
+                $name(!i)
+            }
+        }
+
+        impl Shl<u32> for $name {
+            type Output = $name;
+            fn shl(self, n: u32) -> $name {
+                let $name(i) = self;
+                $name(i.wrapping_shl(n))
+            }
+        }
+
+        impl Shr<u32> for $name {
+            type Output = $name;
+            fn shr(self, n: u32) -> $name {
+                let $name(i) = self;
+                $name(i.wrapping_shr(n))
+            }
+        }
+    };
+
+    (@binop $name:ident, $trait:ident, $fn:ident, $op:ident) => {
+        impl $trait for $name {
+            type Output = $name;
+            fn $fn(self, rhs: $name) -> $name {
+                let $name(lhs) = self;
+                let $name(rhs) = rhs;
+                $name(lhs.$op(rhs))
+            }
+        }
+    };
+
+    // Dividing by zero raises `Division_by_zero`, while dividing the smallest
+    // integer by -1 wraps around.
+    (@divop $name:ident, $trait:ident, $fn:ident, $op:ident) => {
+        impl $trait for $name {
+            type Output = $name;
+            fn $fn(self, rhs: $name) -> $name {
+                let $name(lhs) = self;
+                let $name(rhs) = rhs;
+                if rhs == 0 {
+                    unsafe {
+                        ::mem::unwind_roots();
+                        raw::raise_zero_divide()
+                    }
+                }
+                $name(lhs.$op(rhs))
+            }
+        }
+    };
+}
+
+boxed_int! {
+    /// Binding to the OCaml int32 type
+    int32;
+    /// Value of the OCaml int32 type, converted to Rust
+    Int32(i32) = copy_int32
+}
+
+boxed_int! {
+    /// Binding to the OCaml int64 type
+    int64;
+    /// Value of the OCaml int64 type, converted to Rust
+    Int64(i64) = copy_int64
+}
+
+boxed_int! {
+    /// Binding to the OCaml nativeint type
+    nativeint;
+    /// Value of the OCaml nativeint type, converted to Rust
+    Nativeint(isize) = copy_nativeint
+}
@@ -0,0 +1,97 @@
+use mem::Gc;
+use raw;
+
+/// Binding to the OCaml bytes type
+///
+/// Unlike strings, bytes can be modified in place from the Rust side. As the
+/// content of the bytes might be moved by the GC, borrowing it requires
+/// borrowing the [`Gc`] for the same lifetime, ensuring that no allocation can
+/// happen while the content is borrowed:
+///
+/// ```
+/// fn checksum(gc: &mut Gc, buf: &Bytes) -> int {
+///     let sum = buf.as_bytes(gc).iter().fold(0, |acc, &b| acc + b as isize);
+///     int::from(sum)
+/// }
+///
+/// fn reverse(gc: &mut Gc, buf: &Bytes) {
+///     buf.as_mut_bytes(gc).reverse();
+/// }
+/// ```
+pub struct Bytes(::std::marker::PhantomData<()>);
+
+impl Bytes {
+    /// Returns the length of these bytes.
+    pub fn len(&self) -> usize {
+        unsafe { raw::string_length(::std::mem::transmute::<&Bytes, raw::Value>(self)) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Borrows the content of these bytes.
+    pub fn as_bytes<'a>(&'a self, gc: &'a Gc) -> &'a [u8] {
+        unsafe {
+            let ptr = self as *const Bytes as *const u8;
+            ::std::slice::from_raw_parts(ptr, self.len())
+        }
+    }
+
+    /// Mutably borrows the content of these bytes.
+    pub fn as_mut_bytes<'a>(&'a self, gc: &'a mut Gc) -> &'a mut [u8] {
+        unsafe {
+            let ptr = self as *const Bytes as *mut u8;
+            ::std::slice::from_raw_parts_mut(ptr, self.len())
+        }
+    }
+}
+
+/// Lazy builders for bytes
+pub mod build {
+    use mem::{Build, Gc};
+    use raw;
+    use super::Bytes;
+
+    /// Lazy builder for zero-filled bytes
+    pub struct Zeroed(pub usize);
+
+    /// Lazy builder for bytes copied from a slice
+    pub struct Copied<'b>(pub &'b [u8]);
+
+    unsafe impl Build for Zeroed {
+        type Result = Bytes;
+
+        fn build(self, gc: &mut Gc) -> &Bytes {
+            unsafe {
+                let b = raw::alloc_string(self.0);
+                let raw::Value(ptr) = b;
+                ::std::ptr::write_bytes(ptr as *mut u8, 0, self.0);
+                ::std::mem::transmute(b)
+            }
+        }
+    }
+
+    unsafe impl<'b> Build for Copied<'b> {
+        type Result = Bytes;
+
+        fn build(self, gc: &mut Gc) -> &Bytes {
+            unsafe {
+                let b = raw::alloc_string(self.0.len());
+                let raw::Value(ptr) = b;
+                ::std::ptr::copy_nonoverlapping(self.0.as_ptr(), ptr as *mut u8, self.0.len());
+                ::std::mem::transmute(b)
+            }
+        }
+    }
+}
+
+/// Creates bytes of the given length, filled with zeroes.
+pub fn zeroed(len: usize) -> build::Zeroed {
+    build::Zeroed(len)
+}
+
+/// Creates bytes holding a copy of the given slice.
+pub fn copy<'b>(data: &'b [u8]) -> build::Copied<'b> {
+    build::Copied(data)
+}
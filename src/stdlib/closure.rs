@@ -0,0 +1,82 @@
+use mem::{Build, Gc, P};
+use raw;
+use stdlib::exn::Exn;
+
+/// Binding to the OCaml 'a -> 'b type
+///
+/// Closures received from OCaml can be called from Rust. Exceptions raised by
+/// the closure are caught and returned as errors, rather than unwinding through
+/// the Rust frames:
+///
+/// ```
+/// fn log<'a>(gc: &'a mut Gc, logger: &'a Closure<MlString, unit>, msg: &'a MlString) {
+///     if let Err(exn) = logger.call(gc, msg) {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// The arguments are builders, which are built and rooted along with the
+/// closure before it is called. The closure may itself allocate, so the values
+/// used by the caller after the call must be read from their roots again.
+/// Curried closures of several arguments, such as `'a -> 'b -> 'c`, can be
+/// called at once through [`Closure2`], [`Closure3`], etc.
+pub struct Closure<A, B>(::std::marker::PhantomData<(A, B)>);
+
+// Converts the result of a callback.
+unsafe fn result<'a, B>(res: raw::Value) -> Result<&'a B, &'a Exn> {
+    if raw::is_exception_result(res) {
+        Err(::std::mem::transmute::<raw::Value, &Exn>(raw::extract_exception(res)))
+    } else {
+        Ok(::std::mem::transmute::<raw::Value, &B>(res))
+    }
+}
+
+impl<A, B> Closure<A, B> {
+    /// Applies this closure to an argument.
+    pub fn call<'a, X>(&self, gc: &'a mut Gc, arg: X) -> Result<&'a B, &'a Exn>
+        where X: Build<Result = A> {
+        local! {
+            let f: P<Closure<A, B>> = self;
+            let x: P<A> = alloc!(gc: arg);
+        }
+
+        unsafe { result(gc.callback(|| raw::callback_exn(f.value(), x.value()))) }
+    }
+}
+
+// Each argument type comes with the names of its builder type, of the builder,
+// and of its root.
+macro_rules! closure_type {
+    ( $( $name:ident < $($arg_ty:ident ($builder:ident $arg:ident $root:ident)),+ ; $res:ident > )+ ) => {
+        $(
+            /// Binding to the OCaml type of curried closures of several
+            /// arguments
+            pub struct $name<$($arg_ty,)+ $res>(::std::marker::PhantomData<($($arg_ty,)+ $res)>);
+
+            impl<$($arg_ty,)+ $res> $name<$($arg_ty,)+ $res> {
+                /// Applies this closure to all of its arguments.
+                pub fn call<'a, $($builder),+>(&self, gc: &'a mut Gc, $($arg: $builder),+)
+                                               -> Result<&'a $res, &'a Exn>
+                    where $($builder: Build<Result = $arg_ty>),+ {
+                    local! {
+                        let f: P<$name<$($arg_ty,)+ $res>> = self;
+                        $( let $root: P<$arg_ty> = alloc!(gc: $arg); )+
+                    }
+
+                    unsafe {
+                        let args = [$($root.value()),+];
+                        result(gc.callback(|| raw::callbackn_exn(f.value(), &args)))
+                    }
+                }
+            }
+        )+
+    }
+}
+
+closure_type! {
+    Closure2<A (X x rx), B (Y y ry); C>
+    Closure3<A (X x rx), B (Y y ry), C (Z z rz); D>
+    Closure4<A (X x rx), B (Y y ry), C (Z z rz), D (W w rw); E>
+    Closure5<A (X x rx), B (Y y ry), C (Z z rz), D (W w rw), E (V v rv); F>
+}
@@ -0,0 +1,98 @@
+use block::Block1;
+use matching::Match;
+
+/// Binding to the OCaml ('a, 'b) Either.t type
+pub struct Either<A, B>(::std::marker::PhantomData<(A, B)>);
+
+/// Lazy builders for either values
+pub mod build {
+    use mem::{Build, Gc, P};
+    use super::Either;
+
+    /// Lazy builder for Left() values
+    pub struct Left<U, B> {
+        pub inner: U,
+        pub marker: ::std::marker::PhantomData<B>
+    }
+
+    /// Lazy builder for Right() values
+    pub struct Right<A, V> {
+        pub inner: V,
+        pub marker: ::std::marker::PhantomData<A>
+    }
+
+    unsafe impl<U, B> Build for Left<U, B> where U: Build {
+        type Result = Either<U::Result, B>;
+        fn build(self, gc: &mut Gc) -> &Either<U::Result, B> {
+            local!{ let inner = alloc!(gc: self.inner); }
+            unsafe { gc.raw_alloc(0, &[inner.value()]) }
+        }
+    }
+
+    unsafe impl<A, V> Build for Right<A, V> where V: Build {
+        type Result = Either<A, V::Result>;
+        fn build(self, gc: &mut Gc) -> &Either<A, V::Result> {
+            local!{ let inner = alloc!(gc: self.inner); }
+            unsafe { gc.raw_alloc(1, &[inner.value()]) }
+        }
+    }
+}
+
+pub fn Left<U, B>(of: U) -> build::Left<U, B> {
+    build::Left { inner: of, marker: ::std::marker::PhantomData }
+}
+
+pub fn Right<A, V>(of: V) -> build::Right<A, V> {
+    build::Right { inner: of, marker: ::std::marker::PhantomData }
+}
+
+pub mod tag {
+    use std::convert::TryFrom;
+
+    pub enum Inline {}
+
+    impl TryFrom<isize> for Inline {
+        type Error = ();
+
+        fn try_from(x: isize) -> Result<Inline, ()> {
+            Err(())
+        }
+    }
+
+    #[repr(u8)]
+    pub enum Block {
+        Left = 0,
+        Right = 1
+    }
+
+    impl TryFrom<u8> for Block {
+        type Error = ();
+
+        fn try_from(x: u8) -> Result<Block, ()> {
+            match x {
+                0 => Ok(Block::Left),
+                1 => Ok(Block::Right),
+                _ => Err(())
+            }
+        }
+    }
+}
+
+/// View over the field of a Left or Right value
+pub enum View<'a, A: 'a, B: 'a> {
+    Left(&'a Block1<'a, A>),
+    Right(&'a Block1<'a, B>)
+}
+
+unsafe impl<'a, A: 'a, B: 'a> Match<'a> for Either<A, B> {
+    type InlineTag = tag::Inline;
+    type BlockTag = tag::Block;
+    type Block = View<'a, A, B>;
+
+    unsafe fn view(tag: tag::Block, val: &'a Either<A, B>) -> View<'a, A, B> {
+        match tag {
+            tag::Block::Left => View::Left(&*(val as *const Self as *const _)),
+            tag::Block::Right => View::Right(&*(val as *const Self as *const _))
+        }
+    }
+}
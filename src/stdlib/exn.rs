@@ -0,0 +1,204 @@
+use std::any::Any;
+use mem::{Gc, P};
+use raw;
+use matching::Invalid;
+use stdlib::pervasives::OutOfRange;
+use stdlib::string::MlString;
+
+/// Binding to the OCaml exn type
+///
+/// Exceptions are raised with [`Gc::raise`]. Exceptions defined on the OCaml
+/// side can be retrieved once registered with `Callback.register_exception`:
+///
+/// ```
+/// // let () = Callback.register_exception "my_exn" My_exn
+/// match Exn::named(gc, "my_exn") {
+///     Some(exn) => gc.raise_constant(exn),
+///     None => gc.failwith("My_exn is not registered")
+/// }
+/// ```
+pub struct Exn(::std::marker::PhantomData<()>);
+
+impl Exn {
+    /// Returns the exception registered under the given name, if any.
+    pub fn named<'a>(gc: &'a Gc, name: &str) -> ::std::option::Option<&'a Exn> {
+        let name = match ::std::ffi::CString::new(name) {
+            Ok(name) => name,
+            Err(_) => return ::std::option::Option::None
+        };
+
+        unsafe {
+            let val = raw::named_value(name.as_ptr() as *const u8);
+            if val.is_null() {
+                ::std::option::Option::None
+            } else {
+                ::std::option::Option::Some(::std::mem::transmute::<raw::Value, &Exn>(*val))
+            }
+        }
+    }
+}
+
+/// An OCaml exception to be raised, as a Rust value
+///
+/// Implementations of externs can return `Result<&'a T, E>`, for any error type
+/// `E` that converts into an `MlException`, in which case the error is raised
+/// as an OCaml exception by the [`ml_extern`] wrapper. This allows using the
+/// `?` operator:
+///
+/// ```
+/// fn parse<'a>(gc: &'a mut Gc, s: &'a MlString) -> Result<&'a int, MlException> {
+///     let n: isize = s.to_str(gc)?.parse().map_err(|_| MlException::failure("parse"))?;
+///     Ok(alloc!(gc: int::from(n)))
+/// }
+/// ```
+///
+/// Strings, and the errors of this library, are converted into `Failure`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MlException {
+    /// `Failure msg`
+    Failure(String),
+    /// `Invalid_argument msg`
+    InvalidArgument(String),
+    /// `Not_found`
+    NotFound,
+    /// The exception registered with `Callback.register_exception` under the
+    /// given name, with its string argument if it has one. `Failure` is raised
+    /// instead if no such exception was registered.
+    Registered(&'static str, ::std::option::Option<String>),
+    /// A Rust panic, with its message, raised as the exception registered under
+    /// the name `"rust_panic"`, which should be declared as
+    /// `exception Rust_panic of string`, or as `Failure` if there is none.
+    Panic(String)
+}
+
+impl MlException {
+    pub fn failure<S: Into<String>>(msg: S) -> MlException {
+        MlException::Failure(msg.into())
+    }
+
+    pub fn invalid_argument<S: Into<String>>(msg: S) -> MlException {
+        MlException::InvalidArgument(msg.into())
+    }
+
+    /// Creates the exception for a panic, from the payload caught by
+    /// `catch_unwind`.
+    pub fn from_panic(payload: Box<dyn Any + Send>) -> MlException {
+        let msg = match payload.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(payload) => match payload.downcast_ref::<&'static str>() {
+                ::std::option::Option::Some(msg) => msg.to_string(),
+                ::std::option::Option::None => "Box<Any>".to_string()
+            }
+        };
+        MlException::Panic(msg)
+    }
+
+    /// Raises this exception.
+    ///
+    /// The owned messages are released before the exception is raised.
+    pub fn raise(self, gc: &mut Gc) -> ! {
+        match self {
+            MlException::Failure(msg) => gc.failwith(msg),
+            MlException::InvalidArgument(msg) => gc.invalid_arg(msg),
+            MlException::NotFound => gc.raise_not_found(),
+            MlException::Registered(name, ::std::option::Option::None) => {
+                match Exn::named(gc, name) {
+                    ::std::option::Option::Some(exn) => gc.raise_constant(exn),
+                    ::std::option::Option::None => gc.failwith(name)
+                }
+            }
+            MlException::Registered(name, ::std::option::Option::Some(msg)) => {
+                local! {
+                    let arg: P<MlString> = alloc!(gc: msg);
+                }
+                match Exn::named(gc, name) {
+                    ::std::option::Option::Some(exn) => gc.raise_with_arg(exn, arg.as_ref()),
+                    ::std::option::Option::None => gc.failwith(name)
+                }
+            }
+            MlException::Panic(msg) => {
+                local! {
+                    let arg: P<MlString> = alloc!(gc: msg);
+                }
+                match Exn::named(gc, "rust_panic") {
+                    ::std::option::Option::Some(exn) => gc.raise_with_arg(exn, arg.as_ref()),
+                    ::std::option::Option::None => gc.failwith(arg.as_ref())
+                }
+            }
+        }
+    }
+}
+
+impl ::std::fmt::Display for MlException {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            MlException::Failure(ref msg) => write!(fmt, "Failure({:?})", msg),
+            MlException::InvalidArgument(ref msg) => write!(fmt, "Invalid_argument({:?})", msg),
+            MlException::NotFound => write!(fmt, "Not_found"),
+            MlException::Registered(name, ::std::option::Option::None) => write!(fmt, "{}", name),
+            MlException::Registered(name, ::std::option::Option::Some(ref arg)) => {
+                write!(fmt, "{}({:?})", name, arg)
+            }
+            MlException::Panic(ref msg) => write!(fmt, "Rust_panic({:?})", msg)
+        }
+    }
+}
+
+impl ::std::error::Error for MlException {}
+
+impl ::std::convert::From<String> for MlException {
+    fn from(msg: String) -> MlException {
+        MlException::Failure(msg)
+    }
+}
+
+impl<'a> ::std::convert::From<&'a str> for MlException {
+    fn from(msg: &'a str) -> MlException {
+        MlException::Failure(msg.to_string())
+    }
+}
+
+impl ::std::convert::From<::std::str::Utf8Error> for MlException {
+    fn from(err: ::std::str::Utf8Error) -> MlException {
+        MlException::Failure(err.to_string())
+    }
+}
+
+impl ::std::convert::From<Invalid> for MlException {
+    fn from(err: Invalid) -> MlException {
+        MlException::Failure(err.to_string())
+    }
+}
+
+impl ::std::convert::From<OutOfRange> for MlException {
+    fn from(err: OutOfRange) -> MlException {
+        MlException::Failure(err.to_string())
+    }
+}
+
+/// The return types of the implementations of externs
+///
+/// This is implemented for references to OCaml values, which are returned as
+/// is, and for results of such references, which errors are raised. It should
+/// probably not be used directly, but is used by the [`ml_extern`] macro.
+pub trait Return<'a> {
+    type Value;
+
+    fn into_result(self) -> Result<&'a Self::Value, MlException>;
+}
+
+impl<'a, T> Return<'a> for &'a T {
+    type Value = T;
+
+    fn into_result(self) -> Result<&'a T, MlException> {
+        Ok(self)
+    }
+}
+
+impl<'a, T, E: Into<MlException>> Return<'a> for Result<&'a T, E> {
+    type Value = T;
+
+    fn into_result(self) -> Result<&'a T, MlException> {
+        self.map_err(Into::into)
+    }
+}
@@ -0,0 +1,83 @@
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use mem::{Build, Gc};
+use raw;
+
+/// Binding to the OCaml float type
+///
+/// OCaml floats are boxed: a value of type float is a pointer to a block of tag
+/// `Double_tag` containing the number. As this block only contains the number
+/// itself, a reference to a `float` coming from the OCaml side can simply be
+/// dereferenced to read it.
+///
+/// Values of this type can be used directly in builders, in which case they
+/// will be allocated in a fresh block:
+///
+/// ```
+/// local! {
+///     let x = alloc!(gc: Some(float::from(1.5) * float::from(2.0)));
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub struct float(f64);
+
+impl ::std::fmt::Display for float {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let &float(f) = self;
+        write!(fmt, "{}", f)
+    }
+}
+
+impl From<f64> for float {
+    fn from(f: f64) -> float {
+        float(f)
+    }
+}
+
+impl<'a> From<&'a float> for float {
+    fn from(f: &'a float) -> float {
+        *f
+    }
+}
+
+impl From<float> for f64 {
+    fn from(f: float) -> f64 {
+        let float(f) = f;
+        f
+    }
+}
+
+unsafe impl Build for float {
+    type Result = float;
+
+    fn build(self, gc: &mut Gc) -> &float {
+        let float(f) = self;
+        unsafe { ::std::mem::transmute(raw::copy_double(f)) }
+    }
+}
+
+macro_rules! impl_binop (
+    ( $trait:ident, $fn:ident, $op:tt ) => (
+        impl $trait for float {
+            type Output = float;
+            fn $fn(self, rhs: float) -> float {
+                let float(lhs) = self;
+                let float(rhs) = rhs;
+                float(lhs $op rhs)
+            }
+        }
+    )
+);
+
+impl_binop!(Add, add, +);
+impl_binop!(Sub, sub, -);
+impl_binop!(Mul, mul, *);
+impl_binop!(Div, div, /);
+
+impl Neg for float {
+    type Output = float;
+    fn neg(self) -> float {
+        let float(f) = self;
+        float(-f)
+    }
+}
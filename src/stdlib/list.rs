@@ -1,10 +1,115 @@
 
 #[macro_use] use macros;
-use matching::Match;
+use std::cmp::Ordering;
+use block::Block2;
+use matching::{match_, Match, Matcher};
+use mem::{Build, Gc, P};
+use stdlib::array::Array;
+use stdlib::pervasives::{compare, Comparable};
+use stdlib::tuple::Tuple2;
 
 /// Binding to the OCaml 'a list type
+///
+/// Lists can be walked with ordinary `for` loops, without recursion:
+///
+/// ```
+/// let mut sum = int::from(0);
+/// for x in lst {
+///     sum = sum + int::from(x);
+/// }
+/// ```
+///
+/// and built from any iterator with [`from_iter`]:
+///
+/// ```
+/// let succ: Vec<int> = lst.iter().map(|x| int::from(x) + int::from(1)).collect();
+/// local! {
+///     let succ = alloc!(gc: from_iter(succ));
+/// }
+/// ```
+///
+/// Note that allocating might move the cells of the list: an iterator over a
+/// list must not be used while building a value.
 pub struct List<T>(::std::marker::PhantomData<T>);
 
+impl<T> List<T> {
+    /// Returns an iterator over the elements of this list.
+    pub fn iter<'a>(&'a self) -> ListIter<'a, T> {
+        ListIter { cur: self }
+    }
+
+    /// Returns the number of elements of this list.
+    ///
+    /// This walks the whole list.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the element at index `n`, or `None` if the list is too short.
+    pub fn nth(&self, n: usize) -> ::std::option::Option<&T> {
+        self.iter().nth(n)
+    }
+}
+
+/// Iterator over the elements of a list
+pub struct ListIter<'a, T: 'a> {
+    cur: &'a List<T>
+}
+
+impl<'a, T: 'a> Iterator for ListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> ::std::option::Option<&'a T> {
+        match match_(self.cur) {
+            Ok(Matcher::Inline(tag::Inline::Nil)) => ::std::option::Option::None,
+            Ok(Matcher::Block(View::Cons(&Block2(hd, tl)))) => {
+                self.cur = tl;
+                ::std::option::Option::Some(hd)
+            }
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+// Iterator over a rooted list, which can be used while allocating as long as
+// each element is rooted or consumed before the next allocation.
+struct Cursor<'r, 'p: 'r, T: 'p>(&'r P<'p, List<T>>);
+
+impl<'r, 'p: 'r, T: 'p> Iterator for Cursor<'r, 'p, T> {
+    type Item = &'r T;
+
+    fn next(&mut self) -> ::std::option::Option<&'r T> {
+        let mut iter = self.0.as_ref().iter();
+        let hd = iter.next();
+        self.0.root(iter.cur);
+        hd
+    }
+}
+
+// Builds the value currently held by a root, which is only read once built.
+struct Rooted<'r, 'p: 'r, T: 'p>(&'r P<'p, T>);
+
+unsafe impl<'r, 'p: 'r, T: 'p> Build for Rooted<'r, 'p, T> {
+    type Result = T;
+
+    fn build(self, gc: &mut Gc) -> &T {
+        unsafe { ::std::mem::transmute(self.0.value()) }
+    }
+}
+
+impl<'a, T: 'a> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = ListIter<'a, T>;
+
+    fn into_iter(self) -> ListIter<'a, T> {
+        self.iter()
+    }
+}
+
 pub mod build {
     use mem::{Build, Gc, P};
     use super::List;
@@ -38,8 +143,32 @@ pub mod build {
             unsafe { ::std::mem::transmute(val_int!(0))}
         }
     }
+
+    /// Lazy builder for lists of the elements of an iterator
+    ///
+    /// The list is built iteratively with [`Gc::chain`], making it suitable
+    /// for lists of any length.
+    pub struct FromIter<I>(pub I);
+
+    unsafe impl<I> Build for FromIter<I>
+        where I: Iterator,
+              I::Item: Build {
+        type Result = List<<I::Item as Build>::Result>;
+
+        fn build(self, gc: &mut Gc) -> &Self::Result {
+            // Each cell is built as a list of one element, which tail is then
+            // patched to point to the next cell.
+            let cells = self.0.map(|x| super::Cons(x, super::Nil()));
+            unsafe { gc.chain(1, cells, super::Nil()) }
+        }
+    }
 }
 
+/// Creates a list cell.
+///
+/// Building nested cells recurses as deep as the nesting, which is fine for
+/// lists written by hand. Long lists should instead be built with
+/// [`from_iter`].
 pub fn Cons<U, V>(hd: U, tl: V) -> build::Cons<U, V> {
     build::Cons { hd, tl }
 }
@@ -49,15 +178,28 @@ pub fn Nil<T>() -> build::Nil<T> {
     build::Nil(::std::marker::PhantomData)
 }
 
+/// Creates a list holding the elements of the given iterator.
+pub fn from_iter<I>(iter: I) -> build::FromIter<I::IntoIter>
+    where I: IntoIterator {
+    build::FromIter(iter.into_iter())
+}
+
 pub mod tag {
+    use std::convert::TryFrom;
+
     #[repr(isize)]
     pub enum Inline {
         Nil = 0
     }
 
-    impl From<isize> for Inline {
-        fn from(x: isize) -> Inline {
-            unsafe { ::std::mem::transmute(x) }
+    impl TryFrom<isize> for Inline {
+        type Error = ();
+
+        fn try_from(x: isize) -> Result<Inline, ()> {
+            match x {
+                0 => Ok(Inline::Nil),
+                _ => Err(())
+            }
         }
     }
 
@@ -66,27 +208,174 @@ pub mod tag {
         Cons = 0
     }
 
-    impl From<u8> for Block {
-        fn from(x: u8) -> Block {
-            unsafe { ::std::mem::transmute(x) }
+    impl TryFrom<u8> for Block {
+        type Error = ();
+
+        fn try_from(x: u8) -> Result<Block, ()> {
+            match x {
+                0 => Ok(Block::Cons),
+                _ => Err(())
+            }
         }
     }
 }
 
+/// View over the fields of a list cell
+pub enum View<'a, T: 'a> {
+    Cons(&'a Block2<'a, T, List<T>>)
+}
+
 unsafe impl<'a, T: 'a> Match<'a> for List<T> {
     type InlineTag = tag::Inline;
     type BlockTag = tag::Block;
-    type BlockValue = (T, &'a List<T>);
+    type Block = View<'a, T>;
+
+    unsafe fn view(tag: tag::Block, val: &'a List<T>) -> View<'a, T> {
+        match tag {
+            tag::Block::Cons => View::Cons(&*(val as *const Self as *const _))
+        }
+    }
 }
 
 #[macro_export] macro_rules! list {
     [] => {
-        $crate::matching::Matcher::Inline($crate::stdlib::list::tag::Inline::Nil)
+        ::std::result::Result::Ok(
+            $crate::matching::Matcher::Inline($crate::stdlib::list::tag::Inline::Nil)
+        )
     } ;
     ($hd:ident :: $tl:ident) => {
-        $crate::matching::Matcher::Block(
-            $crate::stdlib::list::tag::Block::Cons,
-            &($hd, $tl)
-        )
+        ::std::result::Result::Ok($crate::matching::Matcher::Block(
+            $crate::stdlib::list::View::Cons(&$crate::block::Block2($hd, $tl))
+        ))
+    } ;
+
+    // Nested patterns, see `ml_match`.
+    (@ml_match $fail:lifetime $v:ident $k:tt) => {
+        ml_match!(@inline $fail $v $k [$crate::stdlib::list::tag::Inline::Nil])
+    } ;
+    (@ml_match $fail:lifetime $v:ident $k:tt $($p:tt)+) => {
+        list!(@ml_match_cons $fail $v $k [] $($p)+)
+    } ;
+    (@ml_match_cons $fail:lifetime $v:ident $k:tt [$($hd:tt)*] :: $($tl:tt)+) => {
+        ml_match!(@block $fail $v $k [$crate::stdlib::list::View::Cons] $($hd)*, $($tl)+)
+    } ;
+    (@ml_match_cons $fail:lifetime $v:ident $k:tt [$($hd:tt)*] $t:tt $($rest:tt)+) => {
+        list!(@ml_match_cons $fail $v $k [$($hd)* $t] $($rest)+)
     }
 }
+
+/// Returns the length of a list.
+pub fn length<T>(lst: &List<T>) -> usize {
+    lst.len()
+}
+
+/// Returns the reversed list.
+pub fn rev<'a, T>(gc: &'a mut Gc, lst: &List<T>) -> &'a List<T> {
+    local! {
+        let cur: P<List<T>> = lst;
+        let acc: P<List<T>> = alloc!(gc: Nil());
+    }
+
+    for x in Cursor(&cur) {
+        let cell = alloc!(gc: Cons(x, acc.as_ref()));
+        acc.root(cell);
+    }
+
+    unsafe { ::std::mem::transmute(acc.value()) }
+}
+
+/// Returns the concatenation of two lists.
+///
+/// The cells of the first list are copied, the second one is shared.
+pub fn append<'a, T>(gc: &'a mut Gc, l1: &List<T>, l2: &List<T>) -> &'a List<T> {
+    local! {
+        let cur: P<List<T>> = l1;
+        let end: P<List<T>> = l2;
+    }
+
+    // The second list is only read once all the cells have been allocated.
+    let cells = Cursor(&cur).map(|x| Cons(x, Nil()));
+    unsafe { gc.chain(1, cells, Rooted(&end)) }
+}
+
+/// Returns the list of the results of `f` applied to the elements of a list.
+///
+/// The value built by `f` is allocated right after it has been called, which
+/// must therefore not allocate itself.
+pub fn map<'a, T, B, F>(gc: &'a mut Gc, lst: &List<T>, mut f: F) -> &'a List<B::Result>
+    where B: Build,
+          F: FnMut(&T) -> B {
+    local! {
+        let cur: P<List<T>> = lst;
+    }
+
+    let cells = Cursor(&cur).map(|x| Cons(f(x), Nil()));
+    unsafe { gc.chain(1, cells, Nil()) }
+}
+
+/// Returns the elements of a list which satisfy `p`, in the same order.
+pub fn filter<'a, T, F>(gc: &'a mut Gc, lst: &List<T>, mut p: F) -> &'a List<T>
+    where F: FnMut(&T) -> bool {
+    local! {
+        let cur: P<List<T>> = lst;
+    }
+
+    let cells = Cursor(&cur).filter(|x| p(x)).map(|x| Cons(x, Nil()));
+    unsafe { gc.chain(1, cells, Nil()) }
+}
+
+/// Folds `f` over the elements of a list, from the first to the last.
+pub fn fold_left<T, A, F>(lst: &List<T>, init: A, f: F) -> A
+    where F: FnMut(A, &T) -> A {
+    lst.iter().fold(init, f)
+}
+
+/// Returns whether an element of a list satisfies `p`.
+pub fn exists<T, F>(lst: &List<T>, p: F) -> bool
+    where F: FnMut(&T) -> bool {
+    lst.iter().any(p)
+}
+
+/// Returns whether a list contains an element structurally equal to `x`.
+pub fn mem<T: Comparable>(lst: &List<T>, x: &T) -> bool {
+    lst.iter().any(|y| compare(x, y) == Ordering::Equal)
+}
+
+/// Returns the value associated to the first key structurally equal to `key`
+/// in a list of pairs.
+///
+/// Unlike in OCaml, a missing key is reported by returning `None` rather than
+/// by raising `Not_found`.
+pub fn assoc<'a, A: Comparable, B>(lst: &'a List<Tuple2<A, B>>, key: &A) -> ::std::option::Option<&'a B> {
+    lst.iter()
+        .find(|pair| compare(key, pair.field0()) == Ordering::Equal)
+        .map(|pair| pair.field1())
+}
+
+/// Sorts a list according to `cmp`.
+///
+/// The sort is stable, and `cmp` must neither allocate nor raise. [`compare`]
+/// can be used to sort [`Comparable`] values in their structural order, just
+/// like `List.sort compare`.
+pub fn sort<'a, T, F>(gc: &'a mut Gc, lst: &List<T>, mut cmp: F) -> &'a List<T>
+    where F: FnMut(&T, &T) -> Ordering {
+    let len = lst.len();
+    local! {
+        let root: P<List<T>> = lst;
+    }
+
+    // The elements are sorted in a block, in which they stay rooted while the
+    // resulting list is built.
+    local! {
+        let buf: P<Array<T>> = unsafe { gc.raw_alloc(0, &vec![val_int!(0); len]) };
+    }
+
+    let mut elems: Vec<&T> = root.as_ref().iter().collect();
+    elems.sort_by(|x, y| cmp(x, y));
+    for (i, x) in elems.into_iter().enumerate() {
+        buf.as_ref().set(i, x);
+    }
+
+    let cells = (0..len).map(|i| Cons(buf.as_ref().get(i), Nil()));
+    unsafe { gc.chain(1, cells, Nil()) }
+}
@@ -9,7 +9,16 @@
 // Public modules, more or less map to the OCaml modules.
 #[macro_use] pub mod pervasives;
 #[macro_use] pub mod list;
-
-// Private modules, just to structure the Rust code, do not match anything in
-// the OCaml module hierarhy.
-mod option;
+#[macro_use] pub mod option;
+pub mod result;
+pub mod either;
+pub mod reference;
+pub mod string;
+pub mod bytes;
+pub mod float;
+pub mod uchar;
+pub mod boxed_int;
+pub mod exn;
+pub mod closure;
+pub mod array;
+#[macro_use] pub mod tuple;
@@ -1,11 +1,75 @@
-use matching::Match;
+use block::Block1;
+use matching::{match_, Match, Matcher};
+use mem::{Build, Gc, P};
 
 /// Binding to the OCaml 'a option type
+///
+/// Options can be inspected with the [`some`] and [`none`] matchers, or
+/// converted to a Rust option of a reference:
+///
+/// ```
+/// match opt.as_option() {
+///     Some(x) => ... ,
+///     None => ... ,
+/// }
+/// ```
 pub struct Option<T>(::std::marker::PhantomData<T>);
 
+impl<T> Option<T> {
+    /// Converts this option into a Rust option of a reference to its content.
+    pub fn as_option(&self) -> ::std::option::Option<&T> {
+        match match_(self) {
+            Ok(Matcher::Inline(tag::Inline::None)) => ::std::option::Option::None,
+            Ok(Matcher::Block(View::Some(&Block1(x)))) => ::std::option::Option::Some(x),
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.as_option().is_some()
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.as_option().is_none()
+    }
+
+    /// Returns the content of this option, or `default` if it is `None`.
+    pub fn unwrap_or<'a>(&'a self, default: &'a T) -> &'a T {
+        self.as_option().unwrap_or(default)
+    }
+
+    /// Applies `f` to the content of this option, returning an option of the
+    /// value it builds.
+    ///
+    /// `None` is returned as is, without allocating.
+    pub fn map<'a, B, F>(&self, gc: &'a mut Gc, f: F) -> &'a Option<B::Result>
+        where B: Build,
+              F: FnOnce(&T) -> B {
+        match self.as_option() {
+            ::std::option::Option::Some(x) => alloc!(gc: Some(f(x))),
+            ::std::option::Option::None => alloc!(gc: None())
+        }
+    }
+
+    /// Applies `f` to the content of this option, returning the option it
+    /// builds.
+    ///
+    /// `f` returns a Rust option of a builder, which is built into an OCaml
+    /// option.
+    pub fn and_then<'a, B, F>(&self, gc: &'a mut Gc, f: F) -> &'a Option<B::Result>
+        where B: Build,
+              F: FnOnce(&T) -> ::std::option::Option<B> {
+        match self.as_option() {
+            ::std::option::Option::Some(x) => alloc!(gc: f(x)),
+            ::std::option::Option::None => alloc!(gc: None())
+        }
+    }
+}
+
 /// Lazy builder for Some() values
 pub mod build {
     use mem::{Build, Gc, P};
+    use super::Option;
 
     pub struct Some<U> {
         pub inner: U
@@ -16,15 +80,26 @@ pub mod build {
 
     unsafe impl<U> Build for Some<U> where U: Build {
         type Result = Option<U::Result>;
-        fn build<'a>(self, gc: &'a mut Gc) -> &'a Option<U::Result> {
+        fn build(self, gc: &mut Gc) -> &Option<U::Result> {
             local!{ let inner = alloc!(gc: self.inner); }
             unsafe { gc.raw_alloc(0, &[inner.value()]) }
         }
     }
 
+    // Rust options of builders build into OCaml options.
+    unsafe impl<U> Build for ::std::option::Option<U> where U: Build {
+        type Result = Option<U::Result>;
+        fn build(self, gc: &mut Gc) -> &Option<U::Result> {
+            match self {
+                ::std::option::Option::Some(x) => alloc!(gc: super::Some(x)),
+                ::std::option::Option::None => alloc!(gc: super::None())
+            }
+        }
+    }
+
     unsafe impl<T> Build for None<T> {
         type Result = Option<T>;
-        fn build<'a>(self, gc: &'a mut Gc) -> &'a Option<T> {
+        fn build(self, gc: &mut Gc) -> &Option<T> {
             unsafe {
                 ::std::mem::transmute(val_int!(0isize))
             }
@@ -42,31 +117,85 @@ pub fn None<T>() -> build::None<T> {
 }
 
 pub mod tag {
+    use std::convert::TryFrom;
+
     #[repr(isize)]
     pub enum Inline {
-        Nil = 0
+        None = 0
     }
 
-    impl From<isize> for Inline {
-        fn from(x: isize) -> Inline {
-            unsafe { ::std::mem::transmute(x) }
+    impl TryFrom<isize> for Inline {
+        type Error = ();
+
+        fn try_from(x: isize) -> Result<Inline, ()> {
+            match x {
+                0 => Ok(Inline::None),
+                _ => Err(())
+            }
         }
     }
 
     #[repr(u8)]
     pub enum Block {
-        Cons = 0
+        Some = 0
     }
 
-    impl From<u8> for Block {
-        fn from(x: u8) -> Block {
-            unsafe { ::std::mem::transmute(x) }
+    impl TryFrom<u8> for Block {
+        type Error = ();
+
+        fn try_from(x: u8) -> Result<Block, ()> {
+            match x {
+                0 => Ok(Block::Some),
+                _ => Err(())
+            }
         }
     }
 }
 
+/// View over the field of a Some value
+pub enum View<'a, T: 'a> {
+    Some(&'a Block1<'a, T>)
+}
+
 unsafe impl<'a, T: 'a> Match<'a> for Option<T> {
     type InlineTag = tag::Inline;
     type BlockTag = tag::Block;
-    type BlockValue = (&'a T);
+    type Block = View<'a, T>;
+
+    unsafe fn view(tag: tag::Block, val: &'a Option<T>) -> View<'a, T> {
+        match tag {
+            tag::Block::Some => View::Some(&*(val as *const Self as *const _))
+        }
+    }
+}
+
+/// Matches the `Some` constructor of options.
+///
+/// `some!(x)` matches the result of [`match_`] on a `Some` value, binding its
+/// content to `x`. Within [`ml_match`], the argument can be a nested pattern.
+#[macro_export] macro_rules! some {
+    // Nested patterns, see `ml_match`.
+    (@ml_match $fail:lifetime $v:ident $k:tt $($p:tt)+) => {
+        ml_match!(@block $fail $v $k [$crate::stdlib::option::View::Some] $($p)+)
+    } ;
+
+    ($x:pat) => {
+        ::std::result::Result::Ok($crate::matching::Matcher::Block(
+            $crate::stdlib::option::View::Some(&$crate::block::Block1($x))
+        ))
+    } ;
+}
+
+/// Matches the `None` constructor of options.
+#[macro_export] macro_rules! none {
+    // Nested patterns, see `ml_match`.
+    (@ml_match $fail:lifetime $v:ident $k:tt) => {
+        ml_match!(@inline $fail $v $k [$crate::stdlib::option::tag::Inline::None])
+    } ;
+
+    () => {
+        ::std::result::Result::Ok(
+            $crate::matching::Matcher::Inline($crate::stdlib::option::tag::Inline::None)
+        )
+    } ;
 }
@@ -1,28 +1,175 @@
 // Re-exports
 pub use stdlib::option::{Option, Some, None};
 pub use stdlib::list::{List, Cons, Nil};
+pub use stdlib::reference::{Ref, ref_};
+pub use stdlib::string::MlString;
+pub use stdlib::bytes::Bytes;
+pub use stdlib::float::float;
+pub use stdlib::uchar::Uchar;
+pub use stdlib::boxed_int::{int32, int64, nativeint, Int32, Int64, Nativeint};
+pub use stdlib::array::Array;
+pub use stdlib::closure::{Closure, Closure2, Closure3, Closure4, Closure5};
+pub use stdlib::exn::{Exn, MlException};
+pub use stdlib::tuple::{Tuple2, Tuple3, Tuple4, Tuple5, Tuple6, Tuple7, Tuple8, Tuple9,
+                        Tuple10, Tuple11, Tuple12};
 
-use std::ops::{Add, Sub, Mul, Div};
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg, Not, BitAnd, BitOr, BitXor, Shl, Shr};
 use mem::Gc;
 
-#[repr(packed)]
+/// Binding to the OCaml int type
+///
+/// OCaml integers are immediate values, one bit narrower than the machine
+/// words. Arithmetic wraps around on overflow, exactly as it does in OCaml,
+/// and dividing by zero raises the `Division_by_zero` exception.
+///
+/// The `Shl` and `Shr` operators correspond to the `lsl` and `asr` OCaml
+/// operators, while `lsr` is provided as a method.
+///
+/// # Comparing, hashing and printing
+///
+/// **The `PartialEq`, `Ord`, `Hash`, `Debug` and `Display` impls are only valid
+/// on values converted with `int::from(&x)`.** The `&int` references received
+/// by externs or handed out by the matching functions hold the integer itself
+/// rather than an address: comparing, hashing or printing them directly
+/// dereferences that integer, and crashes. Convert them first:
+///
+/// ```
+/// fn max<'a>(gc: &'a mut Gc, x: &'a int, y: &'a int) -> &'a int {
+///     alloc!(gc: ::std::cmp::max(int::from(x), int::from(y)))
+/// }
+/// ```
+// References to immediate values hold the value itself rather than a valid
+// address: the binding types of immediates must have an alignment of 1, which
+// the compiler would otherwise assume of these references.
+#[repr(C, packed)]
 #[derive(Clone, Copy)]
 pub struct int(::raw::Value);
 
+/// Error returned when converting an integer which doesn't fit in an `int`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfRange;
+
+impl ::std::fmt::Display for OutOfRange {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(fmt, "integer out of the range of OCaml integers")
+    }
+}
+
+impl ::std::error::Error for OutOfRange {}
+
+impl int {
+    /// The greatest OCaml integer, `max_int`.
+    pub const MAX: isize = isize::MAX >> 1;
+
+    /// The smallest OCaml integer, `min_int`.
+    pub const MIN: isize = isize::MIN >> 1;
+
+    /// Logical shift right, `lsr` in OCaml.
+    ///
+    /// The result is unspecified if `n` is greater than the number of bits of
+    /// an OCaml integer.
+    pub fn lsr(self, n: u32) -> int {
+        // Shifting the tagged representation right moves the lowest bit of the
+        // integer into the tag bit, which only has to be set again.
+        let int(::raw::Value(raw)) = self;
+        int(::raw::Value(((raw as usize).wrapping_shr(n) | 1) as isize))
+    }
+
+    fn get(self) -> isize {
+        let int(::raw::Value(raw)) = self;
+        int_val!(raw)
+    }
+}
+
 impl ::std::fmt::Display for int {
     fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        let &int(::raw::Value(raw)) = self;
-        write!(fmt, "{}", int_val!(raw));
-        Ok(())
+        write!(fmt, "{}", self.get())
+    }
+}
+
+impl ::std::fmt::Debug for int {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(fmt, "{}", self.get())
     }
 }
 
+impl PartialEq for int {
+    fn eq(&self, other: &int) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for int {}
+
+impl PartialOrd for int {
+    fn partial_cmp(&self, other: &int) -> ::std::option::Option<::std::cmp::Ordering> {
+        ::std::option::Option::Some(self.cmp(other))
+    }
+}
+
+impl Ord for int {
+    fn cmp(&self, other: &int) -> ::std::cmp::Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+impl ::std::hash::Hash for int {
+    fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
+
+// Wraps around if the integer doesn't fit, see the `TryFrom` impls for checked
+// conversions.
 impl ::std::convert::From<isize> for int {
     fn from(i: isize) -> int {
         int(val_int!(i))
     }
 }
 
+impl ::std::convert::TryFrom<i64> for int {
+    type Error = OutOfRange;
+
+    fn try_from(i: i64) -> Result<int, OutOfRange> {
+        match isize::try_from(i) {
+            Ok(i) if (int::MIN..=int::MAX).contains(&i) => Ok(int::from(i)),
+            _ => Err(OutOfRange)
+        }
+    }
+}
+
+impl ::std::convert::TryFrom<usize> for int {
+    type Error = OutOfRange;
+
+    fn try_from(i: usize) -> Result<int, OutOfRange> {
+        if i <= int::MAX as usize {
+            Ok(int::from(i as isize))
+        } else {
+            Err(OutOfRange)
+        }
+    }
+}
+
+impl ::std::convert::From<int> for isize {
+    fn from(i: int) -> isize {
+        i.get()
+    }
+}
+
+impl ::std::convert::From<int> for i64 {
+    fn from(i: int) -> i64 {
+        i.get() as i64
+    }
+}
+
+// Arguments of type `int` are handed out as references by the matching
+// functions, which underlying value is the immediate integer itself.
+impl<'a> ::std::convert::From<&'a int> for int {
+    fn from(i: &'a int) -> int {
+        unsafe { ::std::mem::transmute(i) }
+    }
+}
+
 unsafe impl ::mem::Build for int {
     type Result = int;
 
@@ -33,20 +180,234 @@ unsafe impl ::mem::Build for int {
     }
 }
 
+// The operations are performed on the untagged integers, and the results
+// truncated back to the width of OCaml integers by `val_int`, which gives the
+// same wrapping behaviour as OCaml.
 macro_rules! impl_binop (
-    ( $trait:ident, $fn:ident, $op:tt ) => (
+    ( $trait:ident, $fn:ident, $op:ident ) => (
         impl $trait for int {
             type Output = int;
             fn $fn(self, rhs: int) -> int {
-                let int(::raw::Value(lhs)) = self;
-                let int(::raw::Value(rhs)) = rhs;
-                int(val_int!(int_val!(lhs) $op int_val!(rhs)))
+                int(val_int!(self.get().$op(rhs.get())))
             }
         }
     )
 );
 
-impl_binop!(Add, add, +);
-impl_binop!(Sub, sub, -);
-impl_binop!(Mul, mul, *);
-impl_binop!(Div, div, /);
+impl_binop!(Add, add, wrapping_add);
+impl_binop!(Sub, sub, wrapping_sub);
+impl_binop!(Mul, mul, wrapping_mul);
+impl_binop!(BitAnd, bitand, bitand);
+impl_binop!(BitOr, bitor, bitor);
+impl_binop!(BitXor, bitxor, bitxor);
+
+// Dividing `min_int` by `-1` cannot overflow on the untagged integers, which
+// are one bit wider.
+impl Div for int {
+    type Output = int;
+    fn div(self, rhs: int) -> int {
+        if rhs.get() == 0 {
+            unsafe {
+                ::mem::unwind_roots();
+                ::raw::raise_zero_divide()
+            }
+        }
+        int(val_int!(self.get() / rhs.get()))
+    }
+}
+
+impl Rem for int {
+    type Output = int;
+    fn rem(self, rhs: int) -> int {
+        if rhs.get() == 0 {
+            unsafe {
+                ::mem::unwind_roots();
+                ::raw::raise_zero_divide()
+            }
+        }
+        int(val_int!(self.get() % rhs.get()))
+    }
+}
+
+impl Neg for int {
+    type Output = int;
+    fn neg(self) -> int {
+        int(val_int!(self.get().wrapping_neg()))
+    }
+}
+
+impl Not for int {
+    type Output = int;
+    fn not(self) -> int {
+        int(val_int!(!self.get()))
+    }
+}
+
+/// Logical shift left, `lsl` in OCaml.
+impl Shl<u32> for int {
+    type Output = int;
+    fn shl(self, n: u32) -> int {
+        int(val_int!(self.get().wrapping_shl(n)))
+    }
+}
+
+/// Arithmetic shift right, `asr` in OCaml.
+impl Shr<u32> for int {
+    type Output = int;
+    fn shr(self, n: u32) -> int {
+        int(val_int!(self.get().wrapping_shr(n)))
+    }
+}
+
+/// The types which values can be compared structurally without raising.
+///
+/// OCaml's `compare` raises when it reaches a functional or an abstract value,
+/// which would unwind through the Rust frames of the caller.
+///
+/// # Safety
+///
+/// This trait must only be implemented for the types which values can't
+/// contain any.
+pub unsafe trait Comparable {}
+
+unsafe impl Comparable for int {}
+unsafe impl Comparable for MlBool {}
+unsafe impl Comparable for unit {}
+unsafe impl Comparable for MlChar {}
+unsafe impl Comparable for float {}
+unsafe impl Comparable for Uchar {}
+unsafe impl Comparable for MlString {}
+unsafe impl Comparable for Bytes {}
+unsafe impl Comparable for int32 {}
+unsafe impl Comparable for int64 {}
+unsafe impl Comparable for nativeint {}
+unsafe impl<T: Comparable> Comparable for Option<T> {}
+unsafe impl<T: Comparable> Comparable for List<T> {}
+unsafe impl<T: Comparable> Comparable for Ref<T> {}
+unsafe impl<T: Comparable> Comparable for Array<T> {}
+unsafe impl<T: Comparable, E: Comparable> Comparable for ::stdlib::result::Result<T, E> {}
+unsafe impl<A: Comparable, B: Comparable> Comparable for ::stdlib::either::Either<A, B> {}
+
+/// Structural comparison of two values, `compare` in OCaml.
+///
+/// This doesn't allocate, and can't raise on [`Comparable`] values.
+pub fn compare<T: Comparable>(x: &T, y: &T) -> ::std::cmp::Ordering {
+    unsafe {
+        let x = ::std::mem::transmute::<&T, ::raw::Value>(x);
+        let y = ::std::mem::transmute::<&T, ::raw::Value>(y);
+        let ::raw::Value(raw) = ::raw::compare(x, y);
+        int_val!(raw).cmp(&0)
+    }
+}
+
+/// Binding to the OCaml bool type
+///
+/// Just like `int`, booleans are immediate values: references handed out by
+/// the matching functions must be converted before being read.
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct MlBool(::raw::Value);
+
+impl ::std::convert::From<bool> for MlBool {
+    fn from(b: bool) -> MlBool {
+        MlBool(val_int!(b as isize))
+    }
+}
+
+impl<'a> ::std::convert::From<&'a MlBool> for MlBool {
+    fn from(b: &'a MlBool) -> MlBool {
+        unsafe { ::std::mem::transmute(b) }
+    }
+}
+
+impl ::std::convert::From<MlBool> for bool {
+    fn from(b: MlBool) -> bool {
+        let MlBool(::raw::Value(raw)) = b;
+        int_val!(raw) != 0
+    }
+}
+
+unsafe impl ::mem::Build for MlBool {
+    type Result = MlBool;
+
+    fn build(self, gc: &mut Gc) -> &MlBool {
+        unsafe {
+            ::std::mem::transmute(self)
+        }
+    }
+}
+
+/// Binding to the OCaml unit type
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct unit(::raw::Value);
+
+impl ::std::convert::From<()> for unit {
+    fn from(u: ()) -> unit {
+        unit(val_int!(0))
+    }
+}
+
+impl<'a> ::std::convert::From<&'a unit> for unit {
+    fn from(u: &'a unit) -> unit {
+        unit(val_int!(0))
+    }
+}
+
+impl ::std::convert::From<unit> for () {
+    fn from(u: unit) {}
+}
+
+unsafe impl ::mem::Build for unit {
+    type Result = unit;
+
+    fn build(self, gc: &mut Gc) -> &unit {
+        unsafe {
+            ::std::mem::transmute(self)
+        }
+    }
+}
+
+/// Binding to the OCaml char type
+///
+/// OCaml characters are bytes, and are converted to and from `u8`. They can
+/// also be converted to a Rust `char`, in which case they are interpreted as
+/// Latin-1.
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct MlChar(::raw::Value);
+
+impl ::std::convert::From<u8> for MlChar {
+    fn from(c: u8) -> MlChar {
+        MlChar(val_int!(c as isize))
+    }
+}
+
+impl<'a> ::std::convert::From<&'a MlChar> for MlChar {
+    fn from(c: &'a MlChar) -> MlChar {
+        unsafe { ::std::mem::transmute(c) }
+    }
+}
+
+impl ::std::convert::From<MlChar> for u8 {
+    fn from(c: MlChar) -> u8 {
+        let MlChar(::raw::Value(raw)) = c;
+        int_val!(raw) as u8
+    }
+}
+
+impl ::std::convert::From<MlChar> for char {
+    fn from(c: MlChar) -> char {
+        char::from(u8::from(c))
+    }
+}
+
+unsafe impl ::mem::Build for MlChar {
+    type Result = MlChar;
+
+    fn build(self, gc: &mut Gc) -> &MlChar {
+        unsafe {
+            ::std::mem::transmute(self)
+        }
+    }
+}
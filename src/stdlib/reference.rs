@@ -0,0 +1,104 @@
+use block::Block1;
+use matching::Match;
+use raw;
+
+/// Binding to the OCaml 'a ref type
+///
+/// The content of a reference can be replaced from the Rust side with
+/// [`set`], which goes through the write barrier of the GC:
+///
+/// ```
+/// fn incr(gc: &mut Gc, r: &Ref<int>) {
+///     let n = int::from(r.get()) + int::from(1);
+///     r.set(alloc!(gc: n));
+/// }
+/// ```
+pub struct Ref<T>(::std::marker::PhantomData<T>);
+
+impl<T> Ref<T> {
+    /// Returns the content of this reference.
+    pub fn get(&self) -> &T {
+        unsafe {
+            let blk = ::std::mem::transmute::<&Ref<T>, raw::Value>(self);
+            ::std::mem::transmute::<raw::Value, &T>(*raw::field(blk, 0))
+        }
+    }
+
+    /// Replaces the content of this reference.
+    pub fn set(&self, v: &T) {
+        unsafe {
+            let blk = ::std::mem::transmute::<&Ref<T>, raw::Value>(self);
+            raw::modify(raw::field(blk, 0), ::std::mem::transmute::<&T, raw::Value>(v))
+        }
+    }
+}
+
+/// Lazy builder for references
+pub mod build {
+    use mem::{Build, Gc, P};
+    use super::Ref;
+
+    pub struct Ref_<U> {
+        pub inner: U
+    }
+
+    unsafe impl<U> Build for Ref_<U> where U: Build {
+        type Result = Ref<U::Result>;
+        fn build(self, gc: &mut Gc) -> &Ref<U::Result> {
+            local!{ let inner = alloc!(gc: self.inner); }
+            unsafe { gc.raw_alloc(0, &[inner.value()]) }
+        }
+    }
+}
+
+/// Creates a reference, just like the `ref` function of OCaml.
+pub fn ref_<U>(of: U) -> build::Ref_<U> {
+    build::Ref_ { inner: of }
+}
+
+pub mod tag {
+    use std::convert::TryFrom;
+
+    pub enum Inline {}
+
+    impl TryFrom<isize> for Inline {
+        type Error = ();
+
+        fn try_from(x: isize) -> Result<Inline, ()> {
+            Err(())
+        }
+    }
+
+    #[repr(u8)]
+    pub enum Block {
+        Ref = 0
+    }
+
+    impl TryFrom<u8> for Block {
+        type Error = ();
+
+        fn try_from(x: u8) -> Result<Block, ()> {
+            match x {
+                0 => Ok(Block::Ref),
+                _ => Err(())
+            }
+        }
+    }
+}
+
+/// View over the content of a reference
+pub enum View<'a, T: 'a> {
+    Ref(&'a Block1<'a, T>)
+}
+
+unsafe impl<'a, T: 'a> Match<'a> for Ref<T> {
+    type InlineTag = tag::Inline;
+    type BlockTag = tag::Block;
+    type Block = View<'a, T>;
+
+    unsafe fn view(tag: tag::Block, val: &'a Ref<T>) -> View<'a, T> {
+        match tag {
+            tag::Block::Ref => View::Ref(&*(val as *const Self as *const _))
+        }
+    }
+}
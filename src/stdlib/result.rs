@@ -0,0 +1,185 @@
+use block::Block1;
+use matching::{match_, Match, Matcher};
+use mem::{Build, Gc, P};
+
+/// Binding to the OCaml ('a, 'b) result type
+///
+/// Unlike options, results aren't re-exported by [`pervasives`], as they would
+/// shadow the Rust `Result` type and its constructors.
+pub struct Result<T, E>(::std::marker::PhantomData<(T, E)>);
+
+impl<T, E> Result<T, E> {
+    /// Converts this result into a Rust result of references to its content.
+    pub fn as_result(&self) -> ::std::result::Result<&T, &E> {
+        match match_(self) {
+            ::std::result::Result::Ok(Matcher::Block(View::Ok(&Block1(x)))) => {
+                ::std::result::Result::Ok(x)
+            }
+            ::std::result::Result::Ok(Matcher::Block(View::Error(&Block1(e)))) => {
+                ::std::result::Result::Err(e)
+            }
+            ::std::result::Result::Ok(Matcher::Inline(tag)) => match tag {},
+            ::std::result::Result::Err(e) => panic!("{}", e)
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.as_result().is_ok()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.as_result().is_err()
+    }
+
+    /// Returns the content of this result, or `default` if it is an error.
+    pub fn unwrap_or<'a>(&'a self, default: &'a T) -> &'a T {
+        self.as_result().unwrap_or(default)
+    }
+
+    /// Applies `f` to the content of this result, returning a result of the
+    /// value it builds.
+    ///
+    /// Errors are returned as is, without allocating.
+    pub fn map<'a, B, F>(&'a self, gc: &'a mut Gc, f: F) -> &'a Result<B::Result, E>
+        where B: Build,
+              F: FnOnce(&T) -> B {
+        match self.as_result() {
+            ::std::result::Result::Ok(x) => alloc!(gc: Ok(f(x))),
+            // The representation of errors doesn't depend on the type of the
+            // values, the block can be shared.
+            ::std::result::Result::Err(_) => unsafe { &*(self as *const Self as *const _) }
+        }
+    }
+
+    /// Applies `f` to the error of this result, returning a result of the
+    /// value it builds.
+    ///
+    /// Ok values are returned as is, without allocating.
+    pub fn map_error<'a, B, F>(&'a self, gc: &'a mut Gc, f: F) -> &'a Result<T, B::Result>
+        where B: Build,
+              F: FnOnce(&E) -> B {
+        match self.as_result() {
+            ::std::result::Result::Ok(_) => unsafe { &*(self as *const Self as *const _) },
+            ::std::result::Result::Err(e) => alloc!(gc: Error(f(e)))
+        }
+    }
+
+    /// Applies `f` to the content of this result, returning the result it
+    /// builds.
+    ///
+    /// `f` returns a Rust result of builders, which is built into an OCaml
+    /// result.
+    pub fn and_then<'a, B, V, F>(&'a self, gc: &'a mut Gc, f: F) -> &'a Result<B::Result, E>
+        where B: Build,
+              V: Build<Result = E>,
+              F: FnOnce(&T) -> ::std::result::Result<B, V> {
+        match self.as_result() {
+            ::std::result::Result::Ok(x) => alloc!(gc: f(x)),
+            ::std::result::Result::Err(_) => unsafe { &*(self as *const Self as *const _) }
+        }
+    }
+}
+
+/// Lazy builders for result values
+pub mod build {
+    use mem::{Build, Gc, P};
+    use super::Result;
+
+    /// Lazy builder for Ok() values
+    pub struct Ok<U, E> {
+        pub inner: U,
+        pub marker: ::std::marker::PhantomData<E>
+    }
+
+    /// Lazy builder for Error() values
+    pub struct Error<T, V> {
+        pub inner: V,
+        pub marker: ::std::marker::PhantomData<T>
+    }
+
+    unsafe impl<U, E> Build for Ok<U, E> where U: Build {
+        type Result = Result<U::Result, E>;
+        fn build(self, gc: &mut Gc) -> &Result<U::Result, E> {
+            local!{ let inner = alloc!(gc: self.inner); }
+            unsafe { gc.raw_alloc(0, &[inner.value()]) }
+        }
+    }
+
+    // Rust results of builders build into OCaml results.
+    unsafe impl<U, V> Build for ::std::result::Result<U, V> where U: Build, V: Build {
+        type Result = Result<U::Result, V::Result>;
+        fn build(self, gc: &mut Gc) -> &Result<U::Result, V::Result> {
+            match self {
+                ::std::result::Result::Ok(x) => alloc!(gc: super::Ok(x)),
+                ::std::result::Result::Err(e) => alloc!(gc: super::Error(e))
+            }
+        }
+    }
+
+    unsafe impl<T, V> Build for Error<T, V> where V: Build {
+        type Result = Result<T, V::Result>;
+        fn build(self, gc: &mut Gc) -> &Result<T, V::Result> {
+            local!{ let inner = alloc!(gc: self.inner); }
+            unsafe { gc.raw_alloc(1, &[inner.value()]) }
+        }
+    }
+}
+
+pub fn Ok<U, E>(of: U) -> build::Ok<U, E> {
+    build::Ok { inner: of, marker: ::std::marker::PhantomData }
+}
+
+pub fn Error<T, V>(of: V) -> build::Error<T, V> {
+    build::Error { inner: of, marker: ::std::marker::PhantomData }
+}
+
+pub mod tag {
+    use std::convert::TryFrom;
+
+    pub enum Inline {}
+
+    impl TryFrom<isize> for Inline {
+        type Error = ();
+
+        fn try_from(x: isize) -> Result<Inline, ()> {
+            Err(())
+        }
+    }
+
+    #[repr(u8)]
+    pub enum Block {
+        Ok = 0,
+        Error = 1
+    }
+
+    impl TryFrom<u8> for Block {
+        type Error = ();
+
+        fn try_from(x: u8) -> Result<Block, ()> {
+            match x {
+                0 => Ok(Block::Ok),
+                1 => Ok(Block::Error),
+                _ => Err(())
+            }
+        }
+    }
+}
+
+/// View over the field of an Ok or Error value
+pub enum View<'a, T: 'a, E: 'a> {
+    Ok(&'a Block1<'a, T>),
+    Error(&'a Block1<'a, E>)
+}
+
+unsafe impl<'a, T: 'a, E: 'a> Match<'a> for Result<T, E> {
+    type InlineTag = tag::Inline;
+    type BlockTag = tag::Block;
+    type Block = View<'a, T, E>;
+
+    unsafe fn view(tag: tag::Block, val: &'a Result<T, E>) -> View<'a, T, E> {
+        match tag {
+            tag::Block::Ok => View::Ok(&*(val as *const Self as *const _)),
+            tag::Block::Error => View::Error(&*(val as *const Self as *const _))
+        }
+    }
+}
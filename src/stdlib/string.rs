@@ -0,0 +1,85 @@
+use std::str::Utf8Error;
+use mem::{Build, Gc};
+use raw;
+
+/// Binding to the OCaml string type
+///
+/// The content of a string can be borrowed without copying, as a slice of
+/// bytes or, if it is valid UTF-8, as a `&str`. Since an allocation might move
+/// the string, the borrow is bound to the one of the GC, just like for
+/// [`Bytes`]:
+///
+/// ```
+/// fn print(gc: &Gc, s: &MlString) {
+///     println!("{}", s.to_str(gc).unwrap_or("<invalid>"));
+/// }
+/// ```
+///
+/// [`Bytes`]: stdlib::bytes::Bytes
+///
+/// Strings can be allocated from `&str`, `String` or `&[u8]` values:
+///
+/// ```
+/// local! {
+///     let s = alloc!(gc: "hello");
+/// }
+/// ```
+pub struct MlString(::std::marker::PhantomData<()>);
+
+impl MlString {
+    /// Returns the length of this string, in bytes.
+    pub fn len(&self) -> usize {
+        unsafe { raw::string_length(::std::mem::transmute::<&MlString, raw::Value>(self)) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Borrows the content of this string.
+    pub fn as_bytes<'a>(&'a self, gc: &'a Gc) -> &'a [u8] {
+        unsafe {
+            let ptr = self as *const MlString as *const u8;
+            ::std::slice::from_raw_parts(ptr, self.len())
+        }
+    }
+
+    /// Borrows the content of this string, if it is valid UTF-8.
+    pub fn to_str<'a>(&'a self, gc: &'a Gc) -> Result<&'a str, Utf8Error> {
+        ::std::str::from_utf8(self.as_bytes(gc))
+    }
+}
+
+// Allocates a string and fills it with the given bytes.
+fn alloc_string<'a>(gc: &'a mut Gc, bytes: &[u8]) -> &'a MlString {
+    unsafe {
+        let s = raw::alloc_string(bytes.len());
+        let raw::Value(ptr) = s;
+        ::std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr as *mut u8, bytes.len());
+        ::std::mem::transmute(s)
+    }
+}
+
+unsafe impl Build for &[u8] {
+    type Result = MlString;
+
+    fn build(self, gc: &mut Gc) -> &MlString {
+        alloc_string(gc, self)
+    }
+}
+
+unsafe impl Build for &str {
+    type Result = MlString;
+
+    fn build(self, gc: &mut Gc) -> &MlString {
+        alloc_string(gc, self.as_bytes())
+    }
+}
+
+unsafe impl Build for String {
+    type Result = MlString;
+
+    fn build(self, gc: &mut Gc) -> &MlString {
+        alloc_string(gc, self.as_bytes())
+    }
+}
@@ -0,0 +1,186 @@
+use mem::{Build, Gc, P};
+use block::*;
+use matching::Match;
+use stdlib::pervasives::Comparable;
+
+/// Tags of the OCaml tuples
+///
+/// Tuples are always represented by blocks of tag 0, and have no constant
+/// constructor.
+pub mod tag {
+    use std::convert::TryFrom;
+
+    pub enum Inline {}
+
+    impl TryFrom<isize> for Inline {
+        type Error = ();
+
+        fn try_from(x: isize) -> Result<Inline, ()> {
+            Err(())
+        }
+    }
+
+    #[repr(u8)]
+    pub enum Block {
+        Tuple = 0
+    }
+
+    impl TryFrom<u8> for Block {
+        type Error = ();
+
+        fn try_from(x: u8) -> Result<Block, ()> {
+            match x {
+                0 => Ok(Block::Tuple),
+                _ => Err(())
+            }
+        }
+    }
+}
+
+/// View over the fields of a tuple, which is one of the `BlockN` types.
+pub enum View<'a, B: 'a> {
+    Tuple(&'a B)
+}
+
+// Defines the binding for tuples of a given size: the phantom type, its field
+// accessors, its Match impl, and the Build impl of the Rust tuples of builders
+// of the same size.
+macro_rules! tuple_type {
+    ( $(#[$attr:meta])* $name:ident $block:ident
+      ( $( $ty:ident $i:tt $field:ident $x:ident ),+ ) ) => {
+        $(#[$attr])*
+        pub struct $name<$($ty),+>(::std::marker::PhantomData<($($ty,)+)>);
+
+        impl<$($ty),+> $name<$($ty),+> {
+            /// Borrows the fields of this tuple.
+            pub fn fields<'a>(&'a self) -> &'a $block<'a, $($ty),+> {
+                unsafe { ::std::mem::transmute(self) }
+            }
+
+            $(
+                pub fn $field(&self) -> &$ty {
+                    self.fields().$field()
+                }
+            )+
+        }
+
+        unsafe impl<'a, $($ty: 'a),+> Match<'a> for $name<$($ty),+> {
+            type InlineTag = tag::Inline;
+            type BlockTag = tag::Block;
+            type Block = View<'a, $block<'a, $($ty),+>>;
+
+            unsafe fn view(tag: tag::Block, val: &'a Self) -> Self::Block {
+                match tag {
+                    tag::Block::Tuple => View::Tuple(val.fields())
+                }
+            }
+        }
+
+        unsafe impl<$($ty: Comparable),+> Comparable for $name<$($ty),+> {}
+
+        unsafe impl<$($ty: Build),+> Build for ($($ty,)+) {
+            type Result = $name<$($ty::Result),+>;
+
+            fn build(self, gc: &mut Gc) -> &Self::Result {
+                local! {
+                    $( let $x: P<$ty::Result> = alloc!(gc: self.$i); )+
+                }
+
+                unsafe { gc.raw_alloc(0, &[$($x.value()),+]) }
+            }
+        }
+    }
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 2.
+    Tuple2 Block2 (A 0 field0 x0, B 1 field1 x1)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 3.
+    Tuple3 Block3 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 4.
+    Tuple4 Block4 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 5.
+    Tuple5 Block5 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                   E 4 field4 x4)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 6.
+    Tuple6 Block6 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                   E 4 field4 x4, F 5 field5 x5)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 7.
+    Tuple7 Block7 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                   E 4 field4 x4, F 5 field5 x5, G 6 field6 x6)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 8.
+    Tuple8 Block8 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                   E 4 field4 x4, F 5 field5 x5, G 6 field6 x6, H 7 field7 x7)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 9.
+    Tuple9 Block9 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                   E 4 field4 x4, F 5 field5 x5, G 6 field6 x6, H 7 field7 x7,
+                   I 8 field8 x8)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 10.
+    Tuple10 Block10 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                     E 4 field4 x4, F 5 field5 x5, G 6 field6 x6, H 7 field7 x7,
+                     I 8 field8 x8, J 9 field9 x9)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 11.
+    Tuple11 Block11 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                     E 4 field4 x4, F 5 field5 x5, G 6 field6 x6, H 7 field7 x7,
+                     I 8 field8 x8, J 9 field9 x9, K 10 field10 x10)
+}
+
+tuple_type! {
+    /// Binding to the OCaml tuples of size 12.
+    Tuple12 Block12 (A 0 field0 x0, B 1 field1 x1, C 2 field2 x2, D 3 field3 x3,
+                     E 4 field4 x4, F 5 field5 x5, G 6 field6 x6, H 7 field7 x7,
+                     I 8 field8 x8, J 9 field9 x9, K 10 field10 x10,
+                     L 11 field11 x11)
+}
+
+/// Matches tuples of any size.
+///
+/// `tuple!(a, b)` matches the result of [`match_`] on a pair, binding its
+/// fields to `a` and `b`. Within [`ml_match`], the arguments can be nested
+/// patterns:
+///
+/// ```
+/// ml_match! { pair;
+///     tuple!(Some(x), list![]) => ... ,
+///     tuple!(_, y) => ... ,
+/// }
+/// ```
+#[macro_export] macro_rules! tuple {
+    // Nested patterns, see `ml_match`.
+    (@ml_match $fail:lifetime $v:ident $k:tt $($p:tt)+) => {
+        ml_match!(@block $fail $v $k [$crate::stdlib::tuple::View::Tuple] $($p)+)
+    } ;
+
+    ($($x:pat),+) => {
+        ::std::result::Result::Ok($crate::matching::Matcher::Block(
+            $crate::stdlib::tuple::View::Tuple(&ml_block!(pat $($x),+))
+        ))
+    } ;
+}
@@ -0,0 +1,39 @@
+use mem::{Build, Gc};
+use raw;
+
+/// Binding to the OCaml Uchar.t type
+///
+/// Unicode characters are immediate values holding a Unicode scalar value,
+/// and are converted to and from the Rust `char`.
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct Uchar(raw::Value);
+
+impl From<char> for Uchar {
+    fn from(c: char) -> Uchar {
+        Uchar(val_int!(c as isize))
+    }
+}
+
+impl<'a> From<&'a Uchar> for Uchar {
+    fn from(c: &'a Uchar) -> Uchar {
+        unsafe { ::std::mem::transmute(c) }
+    }
+}
+
+// The OCaml side guarantees that the value is a Unicode scalar value, unless
+// created with one of the unsafe functions of the Uchar module.
+impl From<Uchar> for char {
+    fn from(c: Uchar) -> char {
+        let Uchar(raw::Value(raw)) = c;
+        ::std::char::from_u32(int_val!(raw) as u32).expect("invalid Unicode scalar value")
+    }
+}
+
+unsafe impl Build for Uchar {
+    type Result = Uchar;
+
+    fn build(self, gc: &mut Gc) -> &Uchar {
+        unsafe { ::std::mem::transmute(self) }
+    }
+}
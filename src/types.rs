@@ -0,0 +1,612 @@
+//! Bindings derived from OCaml type definitions.
+//!
+//! The [`ml_type`] macro defined in this module generates, from the definition
+//! of an OCaml variant type, the same items that are written by hand for the
+//! types of the standard library (see `stdlib/list.rs` for instance):
+//!
+//! * The phantom binding type itself.
+//! * A `build` module containing a lazy builder implementing [`Build`] for each
+//!   constructor, together with a constructor function of the same name.
+//! * A `chain` module containing a function building long chains of each
+//!   recursive constructor iteratively, and the corresponding builders in
+//!   `build::chain`.
+//! * A `tag` module containing the `Inline` and `Block` tag enums.
+//! * A `View` enum, holding a view over the fields of each non-constant
+//!   constructor, and the implementation of [`Match`] for the binding type.
+//! * A matcher macro, named after the type, to be used in patterns.
+//!
+//! The macro also accepts record type definitions, for which it generates the
+//! items described in the [`record`] module.
+//!
+//! Variant types which constructors are all constant can instead be declared
+//! with the [`ml_enum`] macro, which maps them onto a Rust enum.
+//!
+//! Since those items are generated at the place where the macro is invoked,
+//! and since each type gets its own `build` and `tag` modules, every OCaml type
+//! should be declared in a module of its own.
+
+/// Declares the binding of an OCaml variant type.
+///
+/// ```
+/// mod shape {
+///     ml_type! {
+///         ::shape => type shape = Empty | Circle of int | Rect of int * int;
+///     }
+/// }
+/// ```
+///
+/// The path before the `=>` must be the path of the module in which the macro
+/// is invoked. It is used by the generated matcher macro to refer to the tag
+/// enums, and is therefore expected to be absolute.
+///
+/// Constructors arguments are written with the Rust name of their binding.
+/// Types that are not made of a single identifier must be parenthesized:
+///
+/// ```
+/// ml_type! {
+///     ::tree => type tree<T> = Leaf | Node of (tree<T>) * T * (tree<T>);
+/// }
+/// ```
+///
+/// Values of this type can then be built and matched just like the values of
+/// the standard library types:
+///
+/// ```
+/// local! {
+///     let s = alloc!(gc: Rect(int::from(2), int::from(3)));
+/// }
+///
+/// match match_(s.as_ref()) {
+///     shape![Empty] => ... ,
+///     shape![Rect(w, h)] => ... ,
+///     ...
+///     Err(e) => ... ,
+/// }
+/// ```
+///
+/// The matcher macro is not exported and can only be used after the invocation
+/// of `ml_type`, following the usual scoping rules of macros.
+///
+/// Building nested values recurses as deep as the nesting. Long chains of the
+/// constructors which last argument is the type itself, such as the `More`
+/// constructor of `type stream = End | More of int * stream`, can be built
+/// iteratively with the function of the same name in the generated `chain`
+/// module, from an iterator over their other arguments:
+///
+/// ```
+/// local! {
+///     let s = alloc!(gc: chain::More((0..1000000).map(int::from), End()));
+/// }
+/// ```
+///
+/// Record types can be declared the same way, the path being optional as no
+/// matcher macro is generated for them:
+///
+/// ```
+/// ml_type! {
+///     type point = { x : int; mutable y : int };
+/// }
+/// ```
+///
+/// Every type parameter of a record type must appear in the type of at least
+/// one of its fields.
+///
+/// Every type parameter of a variant type must appear in the arguments of at
+/// least one of its non-constant constructors.
+// NOTE: Constructors are limited to 12 arguments. More names can be added to
+// the list in the `@block` rule if needed.
+#[macro_export] macro_rules! ml_type {
+    ( $($root:ident)* $(:: $seg:ident)+ =>
+      type $name:ident $(< $($param:ident),+ >)* = { $($body:tt)* } $(;)* ) => {
+        ml_type!(@record [$name] [$($($param),+)*] [] $($body)*);
+    };
+    ( type $name:ident $(< $($param:ident),+ >)* = { $($body:tt)* } $(;)* ) => {
+        ml_type!(@record [$name] [$($($param),+)*] [] $($body)*);
+    };
+    ( $($root:ident)* $(:: $seg:ident)+ =>
+      type $name:ident $(< $($param:ident),+ >)* = $($body:tt)+ ) => {
+        ml_type!(@parse [$($root)* $(:: $seg)+] [$name] [$($($param),+)*]
+                 [] [] $($body)+);
+    };
+
+    // Parsing of the constructors, which are sorted into constant and
+    // non-constant ones.
+    (@parse $path:tt $name:tt $params:tt [] [] | $($rest:tt)+) => {
+        ml_type!(@parse $path $name $params [] [] $($rest)+);
+    };
+    (@parse $path:tt $name:tt $params:tt [$($c:tt)*] [$($b:tt)*]
+            $ctor:ident | $($rest:tt)+) => {
+        ml_type!(@parse $path $name $params [$($c)* $ctor] [$($b)*] $($rest)+);
+    };
+    (@parse $path:tt $name:tt $params:tt [$($c:tt)*] [$($b:tt)*]
+            $ctor:ident $(;)*) => {
+        ml_type!(@gen $path $name $params [$($c)* $ctor] [$($b)*]);
+    };
+    (@parse $path:tt $name:tt $params:tt [$($c:tt)*] [$($b:tt)*]
+            $ctor:ident of $f:tt $(* $fs:tt)* | $($rest:tt)+) => {
+        ml_type!(@parse $path $name $params [$($c)*] [$($b)* ($ctor [$f $($fs)*])]
+                 $($rest)+);
+    };
+    (@parse $path:tt $name:tt $params:tt [$($c:tt)*] [$($b:tt)*]
+            $ctor:ident of $f:tt $(* $fs:tt)* $(;)*) => {
+        ml_type!(@gen $path $name $params [$($c)*] [$($b)* ($ctor [$f $($fs)*])]);
+    };
+
+    // Generation of the items.
+    (@gen $path:tt [$name:ident] $params:tt [$($c:ident)*]
+          [$( ($b:ident $fields:tt) )*]) => {
+        ml_type!(@type $name $params);
+
+        ml_type!(@recursive ($) $name);
+
+        /// Lazy builders for the constructors of this type.
+        pub mod build {
+            use super::*;
+            $( ml_type!(@constant builder $name $params $c); )*
+            $( ml_type!(@block builder $name $params $b $fields); )*
+
+            /// Lazy builders for chains of the recursive constructors of this
+            /// type.
+            pub mod chain {
+                use super::super::*;
+                $( ml_type!(@chain builder $name $params $b $fields); )*
+            }
+        }
+
+        $( ml_type!(@constant ctor $name $params $c); )*
+        $( ml_type!(@block ctor $name $params $b $fields); )*
+
+        /// Iterative builders for the recursive constructors of this type.
+        pub mod chain {
+            use super::*;
+            $( ml_type!(@chain ctor $name $params $b $fields); )*
+        }
+
+        pub mod tag {
+            ml_type!(@tag Inline isize $($c)*);
+            ml_type!(@tag Block u8 $($b)*);
+        }
+
+        ml_type!(@match $name $params [$( ($b $fields) )*]);
+        ml_type!(@matcher ($) $path $name [$($c)*] [$($b)*]);
+    };
+
+    (@type $name:ident [$($param:ident),*]) => {
+        /// Binding to an OCaml variant type.
+        #[allow(non_camel_case_types)]
+        pub struct $name<$($param),*>(::std::marker::PhantomData<($($param,)*)>);
+    };
+
+    (@constant builder $name:ident [$($param:ident),*] $c:ident) => {
+        pub struct $c<$($param),*>(pub ::std::marker::PhantomData<($($param,)*)>);
+
+        unsafe impl<$($param),*> $crate::mem::Build for $c<$($param),*> {
+            type Result = $name<$($param),*>;
+
+            fn build(self, gc: &mut $crate::mem::Gc) -> &$name<$($param),*> {
+                unsafe {
+                    ::std::mem::transmute(val_int!(super::tag::Inline::$c as isize))
+                }
+            }
+        }
+    };
+
+    (@constant ctor $name:ident [$($param:ident),*] $c:ident) => {
+        #[allow(non_snake_case)]
+        pub fn $c<$($param),*>() -> build::$c<$($param),*> {
+            build::$c(::std::marker::PhantomData)
+        }
+    };
+
+    // Each argument of a non-constant constructor is associated with a field
+    // name and a type parameter name for its builder.
+    (@block $mode:ident $name:ident $params:tt $b:ident [$($f:tt)*]) => {
+        ml_type!(@zip $mode $name $params $b [] [$($f)*]
+                 [f0 F0 f1 F1 f2 F2 f3 F3 f4 F4 f5 F5
+                  f6 F6 f7 F7 f8 F8 f9 F9 f10 F10 f11 F11]);
+    };
+    (@zip $mode:ident $name:ident $params:tt $b:ident [$($acc:tt)*]
+          [$f:tt $($fs:tt)*] [$v:ident $t:ident $($names:tt)*]) => {
+        ml_type!(@zip $mode $name $params $b [$($acc)* ($v $t $f)]
+                 [$($fs)*] [$($names)*]);
+    };
+    (@zip builder $name:ident [$($param:ident),*] $b:ident
+          [$( ($v:ident $t:ident $f:tt) )*] [] $names:tt) => {
+        pub struct $b<$($param,)* $($t),*> {
+            $( pub $v: $t, )*
+            pub marker: ::std::marker::PhantomData<($($param,)*)>
+        }
+
+        unsafe impl<$($param,)* $($t),*> $crate::mem::Build for $b<$($param,)* $($t),*>
+            where $( $t: $crate::mem::Build<Result = ml_type!(@ty $f)> ),* {
+            type Result = $name<$($param),*>;
+
+            fn build(self, gc: &mut $crate::mem::Gc) -> &$name<$($param),*> {
+                local! {
+                    $( let $v = alloc!(gc: self.$v); )*
+                }
+
+                unsafe {
+                    gc.raw_alloc(super::tag::Block::$b as u8, &[$($v.value()),*])
+                }
+            }
+        }
+    };
+    (@zip ctor $name:ident [$($param:ident),*] $b:ident
+          [$( ($v:ident $t:ident $f:tt) )*] [] $names:tt) => {
+        #[allow(non_snake_case)]
+        pub fn $b<$($param,)* $($t),*>($($v: $t),*) -> build::$b<$($param,)* $($t),*> {
+            build::$b { $($v,)* marker: ::std::marker::PhantomData }
+        }
+    };
+
+    // The recursive constructors are those which last argument is the type
+    // itself. The generated macro expands to the given items for those only.
+    (@recursive ($d:tt) $name:ident) => {
+        #[allow(unused_macros)]
+        macro_rules! ml_type_recursive {
+            ([$name] $d($d items:tt)*) => { $d($d items)* };
+            ([($name $d($d args:tt)*)] $d($d items:tt)*) => { $d($d items)* };
+            ([$d($d f:tt)*] $d($d items:tt)*) => {};
+        }
+    };
+
+    // The arguments but the last are associated with a field name and a type
+    // parameter name, just like for the other builders.
+    (@chain $mode:ident $name:ident $params:tt $b:ident [$($f:tt)*]) => {
+        ml_type!(@chain_zip $mode $name $params $b [] [$($f)*]
+                 [f0 F0 f1 F1 f2 F2 f3 F3 f4 F4 f5 F5
+                  f6 F6 f7 F7 f8 F8 f9 F9 f10 F10 f11 F11]);
+    };
+    (@chain_zip $mode:ident $name:ident $params:tt $b:ident [$($acc:tt)*]
+                [$f:tt $($fs:tt)+] [$v:ident $t:ident $($names:tt)*]) => {
+        ml_type!(@chain_zip $mode $name $params $b [$($acc)* ($v $t $f)]
+                 [$($fs)+] [$($names)*]);
+    };
+    (@chain_zip $mode:ident $name:ident $params:tt $b:ident $acc:tt
+                [$last:tt] $names:tt) => {
+        ml_type_recursive!([$last] ml_type!(@chain_gen $mode $name $params $b $acc););
+    };
+    (@chain_gen builder $name:ident [$($param:ident),*] $b:ident
+                [$( ($v:ident $t:ident $f:tt) )*]) => {
+        pub struct $b<$($param,)* Cells, Tail> {
+            pub cells: Cells,
+            pub tail: Tail,
+            pub marker: ::std::marker::PhantomData<($($param,)*)>
+        }
+
+        unsafe impl<$($param,)* Cells, Tail, $($t),*> $crate::mem::Build
+            for $b<$($param,)* Cells, Tail>
+            where Cells: Iterator<Item = ml_type!(@chain_item $($t)*)>,
+                  Tail: $crate::mem::Build<Result = $name<$($param),*>>,
+                  $( $t: $crate::mem::Build<Result = ml_type!(@ty $f)> ),* {
+            type Result = $name<$($param),*>;
+
+            fn build(self, gc: &mut $crate::mem::Gc) -> &$name<$($param),*> {
+                // Each cell is built with an immediate value as its last
+                // argument, which is then patched to point to the next cell.
+                let cells = self.cells.map(|ml_type!(@chain_pat $($v)*)| {
+                    super::super::$b($($v,)* unsafe { $crate::types::link() })
+                });
+                let link: &[&str] = &[$(stringify!($v)),*];
+                unsafe { gc.chain(link.len(), cells, self.tail) }
+            }
+        }
+    };
+    (@chain_gen ctor $name:ident [$($param:ident),*] $b:ident
+                [$( ($v:ident $t:ident $f:tt) )*]) => {
+        /// Builds a chain of cells of this constructor, iteratively.
+        ///
+        /// The cells are built from the arguments yielded by `cells`, which
+        /// are tuples if there are several of them. The last cell points to
+        /// `tail`.
+        #[allow(non_snake_case)]
+        pub fn $b<$($param,)* Cells, Tail>(cells: Cells, tail: Tail)
+                                           -> build::chain::$b<$($param,)* Cells::IntoIter, Tail>
+            where Cells: IntoIterator {
+            build::chain::$b {
+                cells: cells.into_iter(),
+                tail,
+                marker: ::std::marker::PhantomData
+            }
+        }
+    };
+    (@chain_item) => { () };
+    (@chain_item $t:ident) => { $t };
+    (@chain_item $($t:ident)+) => { ($($t),+) };
+    (@chain_pat) => { () };
+    (@chain_pat $v:ident) => { $v };
+    (@chain_pat $($v:ident)+) => { ($($v),+) };
+
+    (@tag $tag:ident $repr:ident) => {
+        pub enum $tag {}
+
+        impl ::std::convert::TryFrom<$repr> for $tag {
+            type Error = ();
+
+            fn try_from(x: $repr) -> ::std::result::Result<$tag, ()> {
+                Err(())
+            }
+        }
+    };
+    (@tag $tag:ident $repr:ident $($ctor:ident)+) => {
+        #[repr($repr)]
+        pub enum $tag {
+            $($ctor),+
+        }
+
+        impl ::std::convert::TryFrom<$repr> for $tag {
+            type Error = ();
+
+            fn try_from(x: $repr) -> ::std::result::Result<$tag, ()> {
+                $( if x == $tag::$ctor as $repr { return Ok($tag::$ctor) } )+
+                Err(())
+            }
+        }
+    };
+
+    (@match $name:ident [$($param:ident),*] []) => {
+        /// Views over the fields of the non-constant constructors of this type.
+        pub enum View {}
+
+        unsafe impl<'a, $($param: 'a),*> $crate::matching::Match<'a> for $name<$($param),*> {
+            type InlineTag = tag::Inline;
+            type BlockTag = tag::Block;
+            type Block = View;
+
+            unsafe fn view(tag: tag::Block, val: &'a $name<$($param),*>) -> View {
+                match tag {}
+            }
+        }
+    };
+    (@match $name:ident [$($param:ident),*] [$( ($b:ident [$($f:tt)*]) )+]) => {
+        /// Views over the fields of the non-constant constructors of this type.
+        pub enum View<'a, $($param: 'a),*> {
+            $( $b(&'a ml_block!(type 'a; $(ml_type!(@ty $f)),*)) ),*
+        }
+
+        unsafe impl<'a, $($param: 'a),*> $crate::matching::Match<'a> for $name<$($param),*> {
+            type InlineTag = tag::Inline;
+            type BlockTag = tag::Block;
+            type Block = View<'a, $($param),*>;
+
+            unsafe fn view(tag: tag::Block, val: &'a $name<$($param),*>) -> View<'a, $($param),*> {
+                match tag {
+                    $( tag::Block::$b => View::$b(&*(val as *const Self as *const _)) ),*
+                }
+            }
+        }
+    };
+
+    // The `$d` argument is a dollar sign, used to declare the metavariables of
+    // the generated macro.
+    (@matcher ($d:tt) [$($path:tt)*] $name:ident [$($c:ident)*] [$($b:ident)*]) => {
+        macro_rules! $name {
+            (@inline $d c:ident) => {
+                ::std::result::Result::Ok(
+                    $crate::matching::Matcher::Inline($($path)*::tag::Inline::$d c)
+                )
+            };
+            (@block $d c:ident $d($d x:pat),*) => {
+                ::std::result::Result::Ok($crate::matching::Matcher::Block(
+                    $($path)*::View::$d c(&ml_block!(pat $d($d x),*))
+                ))
+            };
+            (@ml_match $d fail:lifetime $d v:ident $d k:tt $d c:ident) => {
+                ml_match!(@inline $d fail $d v $d k [$($path)*::tag::Inline::$d c])
+            };
+            (@ml_match $d fail:lifetime $d v:ident $d k:tt $d c:ident ( $d($d p:tt)+ )) => {
+                ml_match!(@block $d fail $d v $d k [$($path)*::View::$d c] $d($d p)+)
+            };
+            $( [$c] => { $name!(@inline $c) }; )*
+            $( [$b ( $d($d x:pat),* )] => { $name!(@block $b $d($d x),*) }; )*
+        }
+    };
+
+    // Records.
+    (@record $name:tt $params:tt [$($acc:tt)*] mutable $f:ident : $t:tt ; $($rest:tt)*) => {
+        ml_type!(@record $name $params [$($acc)* (mutable $f $t)] $($rest)*);
+    };
+    (@record $name:tt $params:tt [$($acc:tt)*] mutable $f:ident : $t:tt) => {
+        ml_type!(@record $name $params [$($acc)* (mutable $f $t)]);
+    };
+    (@record $name:tt $params:tt [$($acc:tt)*] $f:ident : $t:tt ; $($rest:tt)*) => {
+        ml_type!(@record $name $params [$($acc)* (immutable $f $t)] $($rest)*);
+    };
+    (@record $name:tt $params:tt [$($acc:tt)*] $f:ident : $t:tt) => {
+        ml_type!(@record $name $params [$($acc)* (immutable $f $t)]);
+    };
+    (@record $name:tt $params:tt [$($acc:tt)*]) => {
+        ml_type!(@record_zip $name $params [] [$($acc)*]
+                 [0 F0 1 F1 2 F2 3 F3 4 F4 5 F5 6 F6 7 F7 8 F8 9 F9 10 F10 11 F11
+                  12 F12 13 F13 14 F14 15 F15 16 F16 17 F17 18 F18 19 F19]);
+    };
+    (@record_zip $name:tt $params:tt [$($acc:tt)*] [($m:ident $f:ident $t:tt) $($fs:tt)*]
+                 [$i:tt $v:ident $($names:tt)*]) => {
+        ml_type!(@record_zip $name $params [$($acc)* ($m $f $t $i $v)] [$($fs)*] [$($names)*]);
+    };
+    (@record_zip [$name:ident] $params:tt [$( ($m:ident $f:ident $t:tt $i:tt $v:ident) )*]
+                 [] $names:tt) => {
+        ml_type!(@record_check $name [$($t)*]);
+        ml_type!(@type $name $params);
+        ml_type!(@record_impl $name $params [$( ($f $t $i $v) )*]);
+
+        /// Markers for the fields of this type.
+        pub mod field {
+            use super::*;
+            $( ml_type!(@field $m $name $params $f $t $i); )*
+        }
+
+        /// Lazy builder for this type.
+        pub mod build {
+            use super::*;
+            ml_type!(@record_builder $name $params [$( ($f $t $v) )*]);
+        }
+    };
+    // OCaml stores the records which fields are all floats as flat float
+    // arrays, which the record accessors can't read.
+    (@record_check $name:ident [$(float)+]) => {
+        compile_error!(concat!("the fields of `", stringify!($name), "` are all floats: ",
+                               "such records are stored flat, and can't be bound by ml_type!"));
+    };
+    (@record_check $name:ident $ts:tt) => {};
+    (@record_impl $name:ident [$($param:ident),*] [$( ($f:ident $t:tt $i:tt $v:ident) )*]) => {
+        impl<$($param),*> $name<$($param),*> {
+            $(
+                pub fn $f(&self) -> &ml_type!(@ty $t) {
+                    $crate::record::Record::get(self, field::$f)
+                }
+            )*
+        }
+
+        unsafe impl<$($param),*> $crate::record::Record for $name<$($param),*> {
+            const SIZE: usize = [$($i),*].len();
+        }
+    };
+    (@field $m:ident $name:ident [$($param:ident),*] $f:ident $t:tt $i:tt) => {
+        #[allow(non_camel_case_types)]
+        pub struct $f;
+
+        unsafe impl<$($param),*> $crate::record::Field<$name<$($param),*>> for $f {
+            type Type = ml_type!(@ty $t);
+            const INDEX: usize = $i;
+        }
+
+        ml_type!(@mutable $m $name [$($param),*] $f);
+    };
+    (@mutable mutable $name:ident [$($param:ident),*] $f:ident) => {
+        unsafe impl<$($param),*> $crate::record::Mutable<$name<$($param),*>> for $f {}
+    };
+    (@mutable immutable $name:ident $params:tt $f:ident) => {};
+    (@record_builder $name:ident [$($param:ident),*] [$( ($f:ident $t:tt $v:ident) )*]) => {
+        #[allow(non_camel_case_types)]
+        pub struct $name<$($v),*> {
+            $( pub $f: $v ),*
+        }
+
+        unsafe impl<$($param,)* $($v),*> $crate::mem::Build for $name<$($v),*>
+            where $( $v: $crate::mem::Build<Result = ml_type!(@ty $t)> ),* {
+            type Result = super::$name<$($param),*>;
+
+            fn build(self, gc: &mut $crate::mem::Gc) -> &super::$name<$($param),*> {
+                local! {
+                    $( let $f = alloc!(gc: self.$f); )*
+                }
+
+                unsafe {
+                    gc.raw_alloc(0, &[$($f.value()),*])
+                }
+            }
+        }
+    };
+
+    (@ty ($t:ty)) => { $t };
+    (@ty $t:ty) => { $t };
+}
+
+// Placeholder for the last argument of the cells built by the chain builders,
+// which is patched once the next cell has been built.
+#[doc(hidden)]
+pub struct Link<T>(::std::marker::PhantomData<T>);
+
+// Unsafe as this builds into an immediate value which might not be of type T.
+#[doc(hidden)]
+pub unsafe fn link<T>() -> Link<T> {
+    Link(::std::marker::PhantomData)
+}
+
+unsafe impl<T> ::mem::Build for Link<T> {
+    type Result = T;
+
+    fn build(self, gc: &mut ::mem::Gc) -> &T {
+        unsafe { ::std::mem::transmute(val_int!(0)) }
+    }
+}
+
+/// Declares the binding of an OCaml variant type which constructors are all
+/// constant.
+///
+/// ```
+/// ml_enum! {
+///     type color = Red | Green | Blue => Color;
+/// }
+/// ```
+///
+/// Unlike [`ml_type`], this maps the constructors onto a plain Rust enum, named
+/// after the `=>`, which is the type of the values converted to Rust. The
+/// binding type itself is opaque: just like for `int`, references to such
+/// values handed out by the matching functions are immediate values, and must
+/// be converted before being used:
+///
+/// ```
+/// fn next(gc: &mut Gc, c: &color) -> &color {
+///     match Color::from(c) {
+///         Color::Red => alloc!(gc: Color::Green),
+///         Color::Green => alloc!(gc: Color::Blue),
+///         Color::Blue => alloc!(gc: Color::Red)
+///     }
+/// }
+/// ```
+///
+/// The conversion panics if the value isn't a constructor of the type. Values
+/// can also be matched with [`match_`], in which case they are reported as
+/// [`Inline`] matchers holding the Rust enum.
+///
+/// [`Inline`]: matching::Matcher::Inline
+#[macro_export] macro_rules! ml_enum {
+    ( type $name:ident = $(|)* $($c:ident)|+ => $rust:ident $(;)* ) => {
+        #[allow(non_camel_case_types)]
+        pub struct $name(::std::marker::PhantomData<$rust>);
+
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum $rust {
+            $($c),+
+        }
+
+        impl ::std::convert::TryFrom<isize> for $rust {
+            type Error = ();
+
+            fn try_from(x: isize) -> ::std::result::Result<$rust, ()> {
+                $(
+                    if x == $rust::$c as isize {
+                        return ::std::result::Result::Ok($rust::$c);
+                    }
+                )+
+                ::std::result::Result::Err(())
+            }
+        }
+
+        impl<'a> ::std::convert::From<&'a $name> for $rust {
+            fn from(v: &'a $name) -> $rust {
+                let $crate::raw::Value(raw) =
+                    unsafe { ::std::mem::transmute::<&$name, $crate::raw::Value>(v) };
+                match <$rust as ::std::convert::TryFrom<isize>>::try_from(int_val!(raw)) {
+                    ::std::result::Result::Ok(v) => v,
+                    ::std::result::Result::Err(()) => {
+                        panic!("invalid value {} for type {}", int_val!(raw), stringify!($name))
+                    }
+                }
+            }
+        }
+
+        unsafe impl $crate::mem::Build for $rust {
+            type Result = $name;
+
+            fn build(self, gc: &mut $crate::mem::Gc) -> &$name {
+                unsafe { ::std::mem::transmute(val_int!(self as isize)) }
+            }
+        }
+
+        unsafe impl $crate::stdlib::pervasives::Comparable for $name {}
+
+        unsafe impl<'a> $crate::matching::Match<'a> for $name {
+            type InlineTag = $rust;
+            type BlockTag = $crate::matching::NoTag;
+            type Block = $crate::matching::NoTag;
+
+            unsafe fn view(tag: $crate::matching::NoTag, val: &'a $name) -> $crate::matching::NoTag {
+                match tag {}
+            }
+        }
+    };
+}
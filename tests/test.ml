@@ -1,5 +1,66 @@
 external rust_main: unit -> int option option option = "foo"
 external map: 'a list -> 'a list = "caml_map"
+external nth_from_end: int list -> int -> int option = "caml_nth_from_end"
+external sorted_evens: int list -> int list = "caml_sorted_evens"
+external rev_append: int list -> int list -> int list = "caml_rev_append"
+external lookup: (string * int) list -> string -> int option = "caml_lookup"
+external double_opt: int option -> int option = "caml_double_opt"
+external head_or_zero: int option list -> int = "caml_head_or_zero"
+external check_positive: (int, string) result -> (int, string) result = "caml_check_positive"
+external find: (string * int) list -> string -> int = "caml_find"
+external checked_sqrt: float -> float = "caml_checked_sqrt"
+external raise_named: string -> unit = "caml_raise_named"
+
+exception My_exn
+let () = Callback.register_exception "my_exn" My_exn
+external parse_int: string -> int = "caml_parse_int"
+external head: int list -> int = "caml_head"
+
+exception Parse_error of string
+let () = Callback.register_exception "parse_error" (Parse_error "")
+
+external get: int array -> int -> int = "caml_get"
+
+exception Rust_panic of string
+let () = Callback.register_exception "rust_panic" (Rust_panic "")
+
+external apply_n: (int -> int) -> int -> int -> int = "caml_apply_n"
+external try_apply: (int -> int) -> int -> (int, exn) result = "caml_try_apply"
+external fold_range: (int -> int -> int) -> int -> int -> int = "caml_fold_range"
+external map_or_zero: (int -> int) -> int list -> int list = "caml_map_or_zero"
+external div: int -> int -> int = "caml_div"
+external last: int option list -> int = "caml_last"
+external sum_heads: int list list -> int = "caml_sum_heads"
+external greet: string -> string = "caml_greet"
+external rot13: bytes -> bytes = "caml_rot13"
+external norm: float list -> float option = "caml_norm"
+external cumsum: float array -> float array = "caml_cumsum"
+external max_float: float array -> float = "caml_max_float"
+external squares: int array -> int array = "caml_squares"
+external divmod: int * int -> int * int = "caml_divmod"
+external parse: string -> (int, string) result = "caml_parse"
+external incr: int ref -> int ref = "caml_incr"
+
+type color = Red | Green | Blue
+external next_color: color -> color = "caml_next_color"
+external is_upper: char -> bool = "caml_is_upper"
+external to_upper: Uchar.t -> Uchar.t = "caml_to_upper"
+external offset: int64 -> int32 -> int64 = "caml_offset"
+external gcd: int -> int -> int = "caml_gcd"
+external count_distinct: int list -> int = "caml_count_distinct"
+
+type shape = Empty | Circle of int | Rect of int * int
+external scale: shape -> shape = "caml_scale"
+
+type point = { x : int ; mutable y : int }
+external mirror: point -> point = "caml_mirror"
+
+type stream = End | More of int * stream
+external range: int -> stream = "caml_range"
+
+let rec stream_length acc = function
+  | End -> acc
+  | More (_, s) -> stream_length (acc + 1) s
 
 open Format
 
@@ -26,4 +87,151 @@ let () =
     Format.printf "%a\n"
         (pp_list (fun ff x -> Format.fprintf ff "%d" x) ", ") (map []) ;
 
+    printf "%d\n" (List.length (map (List.init 1_000_000 (fun i -> i)))) ;
+    printf "%d %d\n" (stream_length 0 (range 1_000_000)) (stream_length 0 (range 0)) ;
+    List.iter (fun n ->
+        match nth_from_end lst n with
+          | None -> printf "None\n"
+          | Some x -> printf "Some(%d)\n" x
+    ) [0 ; 2 ; 3] ;
+
+    let pp_ints = pp_list (fun ff x -> Format.fprintf ff "%d" x) ", " in
+    Format.printf "%a\n" pp_ints (sorted_evens [5 ; 8 ; 2 ; 7 ; 4 ; 8]) ;
+    Format.printf "%a\n" pp_ints (rev_append [1 ; 2 ; 3] [4 ; 5]) ;
+    List.iter (fun k ->
+        match lookup ["one", 1 ; "two", 2] k with
+          | None -> printf "None\n"
+          | Some v -> printf "Some(%d)\n" v
+    ) ["two" ; "three"] ;
+
+    List.iter (fun o ->
+        match double_opt o with
+          | None -> printf "None\n"
+          | Some x -> printf "Some(%d)\n" x
+    ) [None ; Some 21 ; Some (-1)] ;
+    printf "%d %d %d\n" (head_or_zero [Some 3 ; None]) (head_or_zero [None]) (head_or_zero []) ;
+    List.iter (fun r ->
+        match check_positive r with
+          | Ok x -> printf "Ok(%d)\n" x
+          | Error e -> printf "Error(%s)\n" e
+    ) [Ok 1 ; Ok 0 ; Error "nan"] ;
+
+    (try printf "%d\n" (find ["one", 1] "one") ; ignore (find [] "one")
+     with Not_found -> printf "Not_found\n") ;
+    (try printf "%g\n" (checked_sqrt 4.) ; ignore (checked_sqrt (-1.))
+     with Invalid_argument msg -> printf "Invalid_argument(%s)\n" msg) ;
+    (try raise_named "my_exn" with My_exn -> printf "My_exn\n") ;
+    (try raise_named "other" with Failure msg -> printf "Failure(%s)\n" msg) ;
+    (try printf "%d\n" (parse_int "42") ; ignore (parse_int "foo")
+     with Parse_error msg -> printf "Parse_error(%s)\n" msg) ;
+    (try printf "%d\n" (head [1 ; 2]) ; ignore (head [])
+     with Not_found -> printf "Not_found\n") ;
+    (try printf "%d\n" (get [| 1 ; 2 |] 1) ; ignore (get [| 1 ; 2 |] 2)
+     with Rust_panic msg -> printf "Rust_panic(%s)\n" msg) ;
+
+    printf "%d\n" (apply_n (fun x -> x * 2) 10 1) ;
+    (try ignore (apply_n (fun x -> if x > 4 then raise Not_found else x + 1) 10 0)
+     with Not_found -> printf "Not_found\n") ;
+    List.iter (fun x ->
+        match try_apply (fun x -> 100 / x) x with
+          | Ok y -> printf "Ok(%d)\n" y
+          | Error e -> printf "Error(%s)\n" (Printexc.to_string e)
+    ) [4 ; 0] ;
+    printf "%d\n" (fold_range (fun acc i -> acc + i) 10 0) ;
+
+    (* Exceptions raised by nested externs, through the Gc or not. *)
+    assert (map_or_zero (fun x -> div 12 x) [1 ; 0 ; 3 ; 0 ; 4] = [12 ; 0 ; 4 ; 0 ; 3]) ;
+    assert (map_or_zero (fun x -> find ["one", 1] (if x = 1 then "one" else "two")) [1 ; 2]
+            = [1 ; 0]) ;
+    assert (map_or_zero (fun x -> get [| 1 ; 2 |] x) [0 ; 1 ; 2] = [1 ; 2 ; 0]) ;
+    (* Exceptions raised several levels deep. *)
+    assert (map_or_zero (fun x ->
+        List.hd (map_or_zero (fun y -> if y = 2 then failwith "two" else div y (x - 3)) [x ; x + 1])
+    ) [1 ; 2 ; 3 ; 4] = [0 ; 0 ; 0 ; 4]) ;
+    (* Exceptions raised by an inner extern, propagated by an outer one which
+       doesn't catch them. *)
+    assert (try ignore (apply_n (fun x -> div x 0) 3 1) ; false
+            with Division_by_zero -> true) ;
+    assert (try ignore (apply_n (fun x ->
+        if x = 0 then failwith "zero" else List.hd (map_or_zero (fun y -> div y 0) [x])) 3 1) ;
+        false
+     with Failure msg -> msg = "zero") ;
+    (* Exceptions raised by OCaml through the outer extern, after nested calls. *)
+    assert (try ignore (map_or_zero (fun x -> ignore (div 1 0) ; x) [1]) ; raise Exit
+            with Exit -> true) ;
+    assert (try ignore (div 1 0) ; false with Division_by_zero -> true) ;
+    (* The roots of the outer extern are still registered, and the ones of the
+       inner externs are gone, once an exception is caught: a major collection
+       after each one must neither lose the list nor scan dead frames. *)
+    assert (map_or_zero (fun x ->
+        Gc.full_major () ;
+        if x mod 2 = 0 then div x 0 else x
+    ) [1 ; 2 ; 3 ; 4 ; 5] = [1 ; 0 ; 3 ; 0 ; 5]) ;
+    assert (try ignore (apply_n (fun x -> Gc.full_major () ; div 6 (3 - x)) 5 1) ; false
+            with Division_by_zero -> true) ;
+    Gc.full_major () ;
+    assert (map_or_zero (fun x -> x * 2) [1 ; 2 ; 3] = [2 ; 4 ; 6]) ;
+
+    printf "%d %d %d\n" (last [None ; Some 4]) (last [Some 1 ; None]) (last []) ;
+    assert (sum_heads [[1 ; 2] ; [] ; [3] ; [-1 ; 5] ; [7]] = 4) ;
+
+    printf "%s\n" (greet "world") ;
+    printf "%s\n" (Bytes.to_string (rot13 (Bytes.of_string "Hello, world!"))) ;
+
+    List.iter (fun v ->
+        match norm v with
+          | None -> printf "None\n"
+          | Some n -> printf "Some(%g)\n" n
+    ) [[3. ; 4.] ; [1.]] ;
+
+    Array.iter (printf "%g ") (cumsum [| 1. ; 2. ; 3. ; 4. |]) ;
+    printf "\n" ;
+    printf "%g\n" (max_float [| 1. ; 4. ; 3. |]) ;
+
+    let arr = [| 1 ; 2 ; 3 |] in
+    let sq = squares arr in
+    Array.iter (printf "%d ") arr ;
+    Array.iter (printf "%d ") sq ;
+    Array.iter (printf "%d ") (squares [||]) ;
+    printf "\n" ;
+
+    let (q, r) = divmod (17, 5) in
+    printf "%d %d\n" q r ;
+    (try ignore (divmod (1, 0)) with Division_by_zero -> printf "Division_by_zero\n") ;
+    printf "%d %d\n" (gcd 12 (-18)) (gcd max_int 0) ;
+    assert (count_distinct [3 ; 1 ; 3 ; 2 ; 1] = 3) ;
+    assert (count_distinct [] = 0) ;
+
+    List.iter (fun s ->
+        match parse s with
+          | Ok n -> printf "Ok(%d)\n" n
+          | Error e -> printf "Error(%s)\n" e
+    ) ["42" ; "foo"] ;
+
+    let r = ref 41 in
+    printf "%d %d\n" !(incr r) !r ;
+
+    List.iter (fun c ->
+        match next_color c with
+          | Red -> printf "Red\n"
+          | Green -> printf "Green\n"
+          | Blue -> printf "Blue\n"
+    ) [Red ; Green ; Blue] ;
+
+    printf "%b %b %x\n" (is_upper 'A') (is_upper 'a')
+        (Uchar.to_int (to_upper (Uchar.of_int 0xe9))) ;
+
+    printf "%Ld\n" (offset 0x1_0000_0000L 13l) ;
+
+    List.iter (fun s ->
+        match scale s with
+          | Empty -> printf "Empty\n"
+          | Circle(r) -> printf "Circle(%d)\n" r
+          | Rect(w, h) -> printf "Rect(%d, %d)\n" w h
+    ) [Empty ; Circle 2 ; Rect (3, 4)] ;
+
+    let p = { x = 1 ; y = 2 } in
+    let q = mirror p in
+    printf "{ x = %d ; y = %d } { x = %d ; y = %d }\n" p.x p.y q.x q.y ;
+
     ()